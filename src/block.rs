@@ -3,6 +3,7 @@
 use crate::date::Date;
 use crate::macros::MACRO_MAP;
 use crate::parse::pdxfile::{parse_pdx_macro, MacroComponent, MacroComponentKind, PdxfileMemory};
+use crate::parse::ParserMemory;
 use crate::token::{Loc, Token};
 
 mod blockitem;
@@ -457,7 +458,7 @@ impl Block {
         &self,
         args: &[(&str, Token)],
         loc: Loc,
-        global: &PdxfileMemory,
+        global: &ParserMemory,
     ) -> Option<Block> {
         let link_index = MACRO_MAP.get_or_insert_loc(loc);
         if let Some(block_source) = &self.source {
@@ -59,10 +59,17 @@ impl Display for Gender {
     }
 }
 
+/// The default youngest age at which characters can marry in history.
+const DEFAULT_MIN_MARRIAGE_AGE: i16 = 16;
+/// The default youngest age at which characters can become parents in history.
+const DEFAULT_MIN_PARENT_AGE: i16 = 14;
+
 #[derive(Debug, Default)]
 #[allow(clippy::struct_field_names)]
 pub struct Characters {
     config_only_born: Option<Date>,
+    config_min_marriage_age: Option<i16>,
+    config_min_parent_age: Option<i16>,
 
     characters: TigerHashMap<&'static str, Character>,
 
@@ -154,6 +161,14 @@ impl Characters {
         self.characters.get(id.as_str()).and_then(|ch| ch.get_faith(date))
     }
 
+    pub fn birth_date(&self, id: &str) -> Option<Date> {
+        self.characters.get(id).and_then(Character::birth_date)
+    }
+
+    pub fn death_date(&self, id: &str) -> Option<Date> {
+        self.characters.get(id).and_then(Character::death_date)
+    }
+
     pub fn validate(&self, data: &Everything) {
         for item in self.characters.values() {
             if item.born_by(self.config_only_born) {
@@ -238,6 +253,12 @@ impl FileHandler<Block> for Characters {
                     self.config_only_born = Some(date);
                 }
             }
+            if let Some(age) = block.get_field_integer("min_marriage_age") {
+                self.config_min_marriage_age = i16::try_from(age).ok();
+            }
+            if let Some(age) = block.get_field_integer("min_parent_age") {
+                self.config_min_parent_age = i16::try_from(age).ok();
+            }
         }
     }
 
@@ -307,6 +328,23 @@ impl Character {
             && self.block.get_field_at_date("death", date).is_none()
     }
 
+    /// Return the date of the first history entry that has the given field.
+    fn first_date_with(&self, field: &str) -> Option<Date> {
+        self.block
+            .iter_definitions()
+            .filter(|(_, block)| block.has_key(field))
+            .filter_map(|(key, _)| Date::try_from(key).ok())
+            .min()
+    }
+
+    pub fn birth_date(&self) -> Option<Date> {
+        self.first_date_with("birth")
+    }
+
+    pub fn death_date(&self) -> Option<Date> {
+        self.first_date_with("death")
+    }
+
     pub fn get_dynasty(&self, date: Date) -> Option<&Token> {
         self.block.get_field_value_at_date("dynasty", date)
     }
@@ -624,9 +662,73 @@ impl Character {
         });
 
         life_events.sort_unstable();
+        self.validate_ages(&life_events, data);
         Self::validate_life(&self.key, life_events);
     }
 
+    /// Check the ages of this character's parents at its birth, and the ages of this character
+    /// and its spouses when they married.
+    fn validate_ages(&self, life_events: &[LifeEvent], data: &Everything) {
+        let Some(birth) = self.birth_date() else {
+            return;
+        };
+
+        let min_parent_age =
+            data.characters.config_min_parent_age.unwrap_or(DEFAULT_MIN_PARENT_AGE);
+        for field in ["father", "mother"] {
+            let Some(parent) = self.block.get_field_value(field) else {
+                continue;
+            };
+            if let Some(parent_birth) = data.characters.birth_date(parent.as_str()) {
+                let age = parent_birth.years_until(birth);
+                if parent_birth > birth {
+                    let msg = format!("{field} {parent} was born after {}", self.key);
+                    warn(ErrorKey::History).msg(msg).loc(parent).push();
+                } else if age < min_parent_age {
+                    let msg = format!(
+                        "{field} {parent} was only {age} years old at the birth of {}",
+                        self.key
+                    );
+                    let info = format!("the minimum is {min_parent_age}; it can be configured with `min_parent_age` in the `characters` section of the config file");
+                    warn(ErrorKey::History).msg(msg).info(info).loc(parent).push();
+                }
+            }
+            if let Some(death) = data.characters.death_date(parent.as_str()) {
+                // The father may have died while the mother was pregnant
+                let too_early =
+                    if field == "mother" { death < birth } else { death.years_until(birth) >= 1 };
+                if too_early {
+                    let msg = format!(
+                        "{field} {parent} died on {death}, before the birth of {}",
+                        self.key
+                    );
+                    warn(ErrorKey::History).msg(msg).loc(parent).push();
+                }
+            }
+        }
+
+        let min_marriage_age =
+            data.characters.config_min_marriage_age.unwrap_or(DEFAULT_MIN_MARRIAGE_AGE);
+        for event in life_events.iter().filter(|e| e.event == LifeEventType::AddSpouse) {
+            let spouse = &event.token;
+            for (who, born) in
+                [(&self.key, Some(birth)), (spouse, data.characters.birth_date(spouse.as_str()))]
+            {
+                let Some(born) = born else {
+                    continue;
+                };
+                let age = born.years_until(event.date);
+                // Marrying before birth is reported elsewhere
+                if born <= event.date && age < min_marriage_age {
+                    let msg =
+                        format!("{who} was only {age} years old when marrying on {}", event.date);
+                    let info = format!("the minimum is {min_marriage_age}; it can be configured with `min_marriage_age` in the `characters` section of the config file");
+                    warn(ErrorKey::History).msg(msg).info(info).loc(spouse).push();
+                }
+            }
+        }
+    }
+
     fn check_pod_flags(&self, _data: &Everything) {
         if self.block.has_key("dna")
             && self.has_trait("nosferatu")
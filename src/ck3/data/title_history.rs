@@ -7,7 +7,7 @@ use crate::date::Date;
 use crate::effect::validate_effect;
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
-use crate::helpers::TigerHashMap;
+use crate::helpers::{TigerHashMap, TigerHashSet};
 use crate::item::Item;
 use crate::parse::ParserMemory;
 use crate::pdxfile::PdxFile;
@@ -51,6 +51,51 @@ impl TitleHistories {
         for item in self.histories.values() {
             item.validate(data);
         }
+        self.validate_timeline(data);
+    }
+
+    /// Replay the holders and lieges of all titles together, to find problems that involve
+    /// more than one title.
+    fn validate_timeline(&self, data: &Everything) {
+        let mut timeline = Timeline::default();
+        for (key, history) in &self.histories {
+            timeline.holders.insert(key, history.holders());
+            timeline.lieges.insert(key, history.changes(&["liege"]));
+            timeline.de_jure_lieges.insert(key, history.changes(&["de_jure_liege"]));
+        }
+        let mut titles: Vec<&str> = self.histories.keys().copied().collect();
+        titles.sort_unstable();
+
+        // A new liege cycle can only appear on a date when one of its lieges is set
+        let mut reported = TigerHashSet::default();
+        for title in &titles {
+            for (date, _) in &timeline.lieges[title] {
+                timeline.check_liege_cycle(title, *date, &mut reported, None);
+            }
+        }
+
+        let mut bookmarks: Vec<(&Token, Date)> = data
+            .database
+            .iter_key_block(Item::Bookmark)
+            .filter_map(|(key, block)| block.get_field_date("start_date").map(|date| (key, date)))
+            .collect();
+        bookmarks.sort_by_key(|(_, date)| *date);
+        for (bookmark, date) in bookmarks {
+            for title in &titles {
+                timeline.check_liege_cycle(title, date, &mut reported, Some(bookmark));
+                timeline.check_bookmark(title, date, bookmark, data);
+            }
+        }
+
+        for title in &titles {
+            if self.histories[title].tier == Tier::County {
+                timeline.check_de_jure_kingdom(title, data);
+            }
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&TitleHistory> {
+        self.histories.get(key)
     }
 
     pub fn verify_has_holder(&self, key: &Token, date: Date, data: &Everything) {
@@ -188,5 +233,374 @@ impl TitleHistory {
         vd.validate_history_blocks(|date, _key, block, data| {
             self.validate_history(date, block, data);
         });
+
+        self.simulate(data);
+    }
+
+    /// Return the values of the given fields in the dated history entries, in date order.
+    fn changes(&self, fields: &[&str]) -> Vec<(Date, &Token)> {
+        let mut changes = Vec::new();
+        for (key, block) in self.block.iter_definitions() {
+            if let Ok(date) = Date::try_from(key) {
+                for field in fields {
+                    for token in block.get_field_values(field) {
+                        changes.push((date, token));
+                    }
+                }
+            }
+        }
+        // This is a stable sort, so changes on the same date stay in file order
+        changes.sort_by_key(|(date, _)| *date);
+        changes
+    }
+
+    fn holders(&self) -> Vec<(Date, &Token)> {
+        self.changes(&["holder", "holder_ignore_head_of_faith_requirement"])
+    }
+
+    /// Follow the title's history in date order, and check that its holders and lieges stay
+    /// consistent between the dates where they are set.
+    fn simulate(&self, data: &Everything) {
+        let holders = self.holders();
+        let lieges = self.changes(&["liege"]);
+        let governments = self.changes(&["government"]);
+
+        // Holders who die while the history still has them holding the title
+        for (i, (date, holder)) in holders.iter().enumerate() {
+            let Some((next, _)) = holders.get(i + 1) else {
+                continue;
+            };
+            if let Some(death) = data.characters.death_date(holder.as_str()) {
+                if *date <= death && death < *next {
+                    let msg =
+                        format!("{holder} died on {death} but holds {} until {next}", self.key);
+                    let info = "the game will pass the title on by succession in the meantime";
+                    warn(ErrorKey::History).weak().msg(msg).info(info).loc(*holder).push();
+                }
+            }
+        }
+
+        // Lieges whose title loses its holder while they are still liege. The date the liege
+        // is set is already checked by `verify_has_holder`.
+        for (i, (date, liege)) in lieges.iter().enumerate() {
+            let Some(liege_history) = data.title_history.get(liege.as_str()) else {
+                continue;
+            };
+            let end = lieges.get(i + 1).map(|(date, _)| *date);
+            for (change_date, holder) in liege_history.holders() {
+                if change_date > *date
+                    && end.map_or(true, |end| change_date < end)
+                    && holder.is("0")
+                {
+                    let msg = format!(
+                        "{liege} has no holder on {change_date} but is liege of {}",
+                        self.key
+                    );
+                    warn(ErrorKey::History)
+                        .msg(msg)
+                        .loc(*liege)
+                        .loc_msg(holder, "from here")
+                        .push();
+                    break;
+                }
+            }
+        }
+
+        // Theocracies must have the same faith as their liege
+        let mut dates: Vec<Date> =
+            holders.iter().chain(&lieges).chain(&governments).map(|(date, _)| *date).collect();
+        dates.sort_unstable();
+        dates.dedup();
+        for date in dates {
+            let Some(government) = value_at(&governments, date) else {
+                continue;
+            };
+            if !government.is("theocracy_government") {
+                continue;
+            }
+            let Some(holder) = value_at(&holders, date).filter(|h| !h.is("0")) else {
+                continue;
+            };
+            let Some(liege) = value_at(&lieges, date).filter(|l| !l.is("0")) else {
+                continue;
+            };
+            let Some(liege_holder) = data
+                .title_history
+                .get(liege.as_str())
+                .and_then(|history| value_at(&history.holders(), date))
+            else {
+                continue;
+            };
+            let faith = data.characters.get_faith(holder, date);
+            let liege_faith = data.characters.get_faith(liege_holder, date);
+            if let (Some(faith), Some(liege_faith)) = (faith, liege_faith) {
+                if faith != liege_faith {
+                    let msg = format!(
+                        "theocratic holder {holder} of {} has faith {faith} on {date}, but liege {liege_holder} has faith {liege_faith}",
+                        self.key
+                    );
+                    warn(ErrorKey::History).msg(msg).loc(holder).push();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// The history of all titles, for checks that involve more than one title.
+#[derive(Default)]
+struct Timeline<'a> {
+    holders: TigerHashMap<&'a str, Vec<(Date, &'a Token)>>,
+    lieges: TigerHashMap<&'a str, Vec<(Date, &'a Token)>>,
+    de_jure_lieges: TigerHashMap<&'a str, Vec<(Date, &'a Token)>>,
+}
+
+impl<'a> Timeline<'a> {
+    /// Return the value of a title's history field at `date`, treating `0` as no value.
+    fn get(
+        map: &TigerHashMap<&'a str, Vec<(Date, &'a Token)>>,
+        title: &str,
+        date: Date,
+    ) -> Option<&'a Token> {
+        value_at(map.get(title)?, date).filter(|token| !token.is("0"))
+    }
+
+    /// Report a liege cycle that `title` is part of at `date`. Each cycle is reported only once.
+    fn check_liege_cycle(
+        &self,
+        title: &str,
+        date: Date,
+        reported: &mut TigerHashSet<Vec<&'a str>>,
+        bookmark: Option<&Token>,
+    ) {
+        let mut chain: Vec<&Token> = Vec::new();
+        let mut current = title;
+        while let Some(liege) = Self::get(&self.lieges, current, date) {
+            if let Some(pos) = chain.iter().position(|token| token.is(liege.as_str())) {
+                // The cycle may be further up the chain, not including `title`
+                let mut cycle: Vec<&str> =
+                    chain[pos..].iter().map(|token| token.as_str()).collect();
+                cycle.sort_unstable();
+                if reported.insert(cycle) {
+                    let msg = format!("titles are each other's liege on {date}");
+                    let mut report = err(ErrorKey::Loop).msg(msg).loc(liege);
+                    for token in &chain[pos..] {
+                        report = report.loc_msg(*token, "liege");
+                    }
+                    if let Some(bookmark) = bookmark {
+                        report = report.loc_msg(bookmark, "at this bookmark");
+                    }
+                    report.push();
+                }
+                return;
+            }
+            chain.push(liege);
+            current = liege.as_str();
+        }
+    }
+
+    /// Check that at the start date of a bookmark, the title's holder is alive and its liege has
+    /// a living holder.
+    fn check_bookmark(&self, title: &str, date: Date, bookmark: &Token, data: &Everything) {
+        // If the title gets a new holder later, `TitleHistory::simulate` already reported a
+        // holder who died before then.
+        let replaced_later =
+            self.holders.get(title).is_some_and(|holders| holders.iter().any(|(d, _)| *d > date));
+        if let Some(holder) = Self::get(&self.holders, title, date).filter(|_| !replaced_later) {
+            if let Some(death) = data.characters.death_date(holder.as_str()) {
+                if death <= date {
+                    let msg = format!(
+                        "holder {holder} of {title} died on {death}, before bookmark {bookmark}"
+                    );
+                    let info = "the game will pass the title on by succession when the game starts";
+                    warn(ErrorKey::History)
+                        .msg(msg)
+                        .info(info)
+                        .loc(holder)
+                        .loc_msg(bookmark, "bookmark")
+                        .push();
+                }
+            }
+        }
+        if let Some(liege) = Self::get(&self.lieges, title, date) {
+            if Self::get(&self.holders, liege.as_str(), date).is_none() {
+                let msg = format!(
+                    "{liege} has no holder at bookmark {bookmark}, but is liege of {title}"
+                );
+                warn(ErrorKey::History).msg(msg).loc(liege).loc_msg(bookmark, "bookmark").push();
+            }
+        }
+    }
+
+    /// Return the de jure liege of a title at `date`, from its history or else from its place
+    /// in the landed titles.
+    fn de_jure_liege(&self, title: &str, date: Date, data: &Everything) -> Option<&'a str> {
+        if let Some(changes) = self.de_jure_lieges.get(title) {
+            if let Some(liege) = value_at(changes, date) {
+                return (!liege.is("0")).then(|| liege.as_str());
+            }
+        }
+        data.titles.get(title).and_then(|title| title.parent)
+    }
+
+    /// Report when a county's de jure duchy is changed to one in a different kingdom.
+    fn check_de_jure_kingdom(&self, county: &str, data: &Everything) {
+        let Some(changes) = self.de_jure_lieges.get(county) else {
+            return;
+        };
+        let mut old_duchy = data.titles.get(county).and_then(|title| title.parent);
+        for (date, duchy) in changes {
+            if duchy.is("0") {
+                old_duchy = None;
+                continue;
+            }
+            if let Some(old_duchy) = old_duchy.filter(|old| !duchy.is(old)) {
+                let old_kingdom = self.de_jure_liege(old_duchy, *date, data);
+                let new_kingdom = self.de_jure_liege(duchy.as_str(), *date, data);
+                if let (Some(old_kingdom), Some(new_kingdom)) = (old_kingdom, new_kingdom) {
+                    if old_kingdom != new_kingdom {
+                        let msg = format!(
+                            "{county} moves to {duchy} on {date}, which takes it from {old_kingdom} to {new_kingdom}"
+                        );
+                        let info = "check that the county was meant to change de jure kingdom";
+                        warn(ErrorKey::History).weak().msg(msg).info(info).loc(*duchy).push();
+                    }
+                }
+            }
+            old_duchy = Some(duchy.as_str());
+        }
+    }
+}
+
+/// Return the value that was most recently set on or before `date`.
+fn value_at<'a>(changes: &[(Date, &'a Token)], date: Date) -> Option<&'a Token> {
+    changes.iter().rev().find(|(d, _)| *d <= date).map(|(_, token)| *token)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::fileset::FileKind;
+    use crate::report::{take_reports, TEST_REPORTS_LOCK};
+    use crate::token::Loc;
+
+    fn token(s: &'static str) -> Token {
+        let loc =
+            Loc::for_file(PathBuf::from("test.txt"), FileKind::Mod, PathBuf::from("test.txt"));
+        Token::from_static_str(s, loc)
+    }
+
+    /// Run `check` with the titles and characters of the test files loaded, and return the
+    /// messages it reported.
+    fn reports(check: impl FnOnce(&Everything)) -> Vec<String> {
+        let _guard = TEST_REPORTS_LOCK.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let vanilla_dir = PathBuf::from("tests/files/ck3");
+        let mut data =
+            Everything::new(None, Some(&vanilla_dir), Path::new("tests/files/mod3"), Vec::new())
+                .unwrap();
+        data.load_all();
+        take_reports();
+        check(&data);
+        take_reports().into_iter().map(|report| report.msg).collect()
+    }
+
+    #[test]
+    fn test_value_at() {
+        let loc =
+            Loc::for_file(PathBuf::from("test.txt"), FileKind::Mod, PathBuf::from("test.txt"));
+        let first = Token::from_static_str("1", loc.clone());
+        let second = Token::from_static_str("2", loc.clone());
+        let third = Token::from_static_str("3", loc);
+        // Changes on the same date are in file order, and the last one wins
+        let changes = vec![
+            (Date::new(1000, 1, 1), &first),
+            (Date::new(1010, 6, 1), &second),
+            (Date::new(1010, 6, 1), &third),
+        ];
+        assert!(value_at(&changes, Date::new(999, 12, 31)).is_none());
+        assert!(value_at(&changes, Date::new(1000, 1, 1)).unwrap().is("1"));
+        assert!(value_at(&changes, Date::new(1010, 5, 31)).unwrap().is("1"));
+        assert!(value_at(&changes, Date::new(1010, 6, 1)).unwrap().is("3"));
+        assert!(value_at(&changes, Date::new(1100, 1, 1)).unwrap().is("3"));
+        assert!(value_at(&[], Date::new(1000, 1, 1)).is_none());
+    }
+
+    #[test]
+    fn test_liege_cycle() {
+        let (county, duchy, kingdom) = (token("c_a"), token("d_b"), token("k_c"));
+        let bookmark = token("bm_test");
+        let date = Date::new(1000, 1, 1);
+        let mut timeline = Timeline::default();
+        timeline.lieges.insert("c_a", vec![(date, &duchy)]);
+        timeline.lieges.insert("d_b", vec![(date, &kingdom)]);
+        timeline.lieges.insert("k_c", vec![(date, &duchy)]);
+        timeline.lieges.insert("d_x", vec![(date, &county)]);
+
+        let messages = reports(|_| {
+            let mut reported = TigerHashSet::default();
+            // The cycle is further up the chain than the starting title
+            timeline.check_liege_cycle("c_a", date, &mut reported, None);
+            timeline.check_liege_cycle("d_x", date, &mut reported, None);
+            timeline.check_liege_cycle("k_c", date, &mut reported, None);
+            // A cycle that was already reported is not reported again at a bookmark
+            let later = Date::new(1066, 9, 15);
+            timeline.check_liege_cycle("d_b", later, &mut reported, Some(&bookmark));
+            assert_eq!(reported.len(), 1);
+            assert!(reported.contains(&vec!["d_b", "k_c"]));
+        });
+        assert_eq!(messages, vec!["titles are each other's liege on 1000.1.1"]);
+    }
+
+    #[test]
+    fn test_de_jure_kingdom() {
+        let (other, moved, back) = (token("d_test_other"), token("d_test2"), token("d_test"));
+        let kingdom = token("k_test");
+        let mut timeline = Timeline::default();
+        // Moving within the same kingdom is fine
+        timeline.de_jure_lieges.insert(
+            "c_test",
+            vec![
+                (Date::new(1000, 1, 1), &other),
+                (Date::new(1010, 1, 1), &moved),
+                (Date::new(1020, 1, 1), &back),
+            ],
+        );
+        let messages = reports(|data| timeline.check_de_jure_kingdom("c_test", data));
+        assert_eq!(
+            messages,
+            vec![
+                "c_test moves to d_test2 on 1010.1.1, which takes it from k_test to k_test2",
+                "c_test moves to d_test on 1020.1.1, which takes it from k_test2 to k_test",
+            ]
+        );
+
+        // Not a kingdom change if the new duchy was moved into the same kingdom first
+        timeline.de_jure_lieges.insert("d_test2", vec![(Date::new(1005, 1, 1), &kingdom)]);
+        let messages = reports(|data| timeline.check_de_jure_kingdom("c_test", data));
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn test_bookmark_dead_holder() {
+        let (dead, alive) = (token("1004"), token("1006"));
+        let bookmark = token("bm_test");
+        let date = Date::new(1025, 1, 1);
+        let mut timeline = Timeline::default();
+        timeline.holders.insert("c_test", vec![(Date::new(1010, 1, 1), &dead)]);
+        let messages = reports(|data| timeline.check_bookmark("c_test", date, &bookmark, data));
+        assert_eq!(
+            messages,
+            vec!["holder 1004 of c_test died on 1020.1.1, before bookmark bm_test"]
+        );
+
+        // The history gives the title a new holder later, which `simulate` reports instead
+        timeline.holders.insert(
+            "c_test",
+            vec![(Date::new(1010, 1, 1), &dead), (Date::new(1030, 1, 1), &alive)],
+        );
+        let messages = reports(|data| timeline.check_bookmark("c_test", date, &bookmark, data));
+        assert!(messages.is_empty());
     }
 }
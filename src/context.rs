@@ -583,6 +583,11 @@ impl ScopeContext {
         self.scopes_reason().0
     }
 
+    /// Return the possible scope types of `root`.
+    pub fn root_scopes(&self) -> Scopes {
+        self.resolve_root().0
+    }
+
     /// Return the possible scope types of `root`, and the reason why we think it has those types
     #[doc(hidden)]
     fn resolve_root(&self) -> (Scopes, &Reason) {
@@ -1,11 +1,13 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::block::{Block, BV};
 use crate::everything::Everything;
-use crate::fileset::{FileEntry, FileHandler};
+use crate::fileset::{FileEntry, FileHandler, FileKind};
 use crate::game::Game;
 use crate::helpers::{dup_error, TigerHashMap, TigerHashSet};
 use crate::item::Item;
+use crate::mesh::MeshInfo;
 use crate::parse::ParserMemory;
 use crate::pdxfile::PdxFile;
 use crate::report::{warn, Confidence, ErrorKey, Severity};
@@ -55,6 +57,12 @@ impl Assets {
         self.assets.values().filter(|item| item.key.is("pdxmesh")).map(|item| &item.name)
     }
 
+    /// Return the path of the `.mesh` file used by the named pdxmesh, if any.
+    pub fn get_mesh_file(&self, key: &str) -> Option<PathBuf> {
+        let asset = self.assets.get(key).filter(|asset| asset.key.is("pdxmesh"))?;
+        asset.block.get_field_value("file").map(|file| asset.file_path(file))
+    }
+
     pub fn entity_exists(&self, key: &str) -> bool {
         if let Some(asset) = self.assets.get(key) {
             asset.key.is("entity")
@@ -178,13 +186,41 @@ impl Asset {
         Self { key, name, block }
     }
 
+    fn file_path(&self, file: &Token) -> PathBuf {
+        self.key.loc.pathname().smart_join_parent(file.as_str())
+    }
+
+    /// Read the binary mesh or animation file at `path`.
+    /// This is only done for the mod's own assets, because reading all of the base game's files
+    /// would take too long.
+    fn load_binary(&self, data: &Everything, path: &Path) -> Option<Arc<MeshInfo>> {
+        if self.key.loc.kind < FileKind::LoadedMod(0) {
+            return None;
+        }
+        let entry = data.fileset.get_entry(&path.to_string_lossy())?;
+        data.meshes.get(entry)
+    }
+
+    /// Report problems with the binary animation file at `path`, for files whose contents are
+    /// not otherwise needed. Like [`Asset::load_binary`], this is only done for the mod's assets.
+    fn validate_binary(&self, data: &Everything, path: &Path) {
+        if self.key.loc.kind < FileKind::LoadedMod(0) {
+            return;
+        }
+        if let Some(entry) = data.fileset.get_entry(&path.to_string_lossy()) {
+            data.meshes.validate(entry);
+        }
+    }
+
     pub fn validate_mesh(&self, data: &Everything) {
         let mut vd = Validator::new(&self.block, data);
         vd.field_value("name");
         vd.req_field("file");
+        let mut mesh = None;
         if let Some(token) = vd.field_value("file") {
-            let path = self.key.loc.pathname().smart_join_parent(token.as_str());
+            let path = self.file_path(token);
             data.verify_exists_implied(Item::File, &path.to_string_lossy(), token);
+            mesh = self.load_binary(data, &path).map(|info| (info, token.clone()));
         }
         vd.field_numeric("scale");
         vd.field_numeric("cull_distance");
@@ -200,17 +236,27 @@ impl Asset {
             });
         });
 
-        vd.multi_field_validated_block("meshsettings", validate_meshsettings);
+        vd.multi_field_validated_block("meshsettings", |block, data| {
+            validate_meshsettings(block, data);
+            if let Some((info, file)) = &mesh {
+                validate_shape_name(block.get_field_value("name"), info, file);
+            }
+        });
         vd.multi_field_validated_block("blend_shape", |block, data| {
             let mut vd = Validator::new(block, data);
             vd.req_field("id");
             vd.req_field("type");
             vd.field_value("id");
+            let mut blend_mesh = None;
             if let Some(token) = vd.field_value("type") {
-                let path = self.key.loc.pathname().smart_join_parent(token.as_str());
+                let path = self.file_path(token);
                 data.verify_exists_implied(Item::File, &path.to_string_lossy(), token);
+                blend_mesh = self.load_binary(data, &path).map(|info| (info, token.clone()));
+            }
+            let shape = vd.field_value("data").filter(|token| !token.is(""));
+            if let Some((info, file)) = &blend_mesh {
+                validate_shape_name(shape, info, file);
             }
-            vd.field_value("data"); // TODO
         });
 
         vd.multi_field_validated_block("animation", |block, data| {
@@ -219,8 +265,9 @@ impl Asset {
             vd.req_field("type");
             vd.field_value("id");
             if let Some(token) = vd.field_value("type") {
-                let path = self.key.loc.pathname().smart_join_parent(token.as_str());
+                let path = self.file_path(token);
                 data.fileset.verify_exists_implied_crashes(&path.to_string_lossy(), token);
+                self.validate_binary(data, &path);
             }
         });
         vd.multi_field_validated_block("additive_animation", |block, data| {
@@ -229,8 +276,9 @@ impl Asset {
             vd.req_field("type");
             vd.field_value("id");
             if let Some(token) = vd.field_value("type") {
-                let path = self.key.loc.pathname().smart_join_parent(token.as_str());
+                let path = self.file_path(token);
                 data.verify_exists_implied(Item::File, &path.to_string_lossy(), token);
+                self.validate_binary(data, &path);
             }
         });
 
@@ -247,6 +295,11 @@ impl Asset {
 
         vd.field_value("name");
         vd.field_item("pdxmesh", Item::Pdxmesh);
+        let mesh = self.block.get_field_value("pdxmesh").and_then(|pdxmesh| {
+            let path = data.assets.get_mesh_file(pdxmesh.as_str())?;
+            let info = self.load_binary(data, &path)?;
+            Some((info, pdxmesh))
+        });
         vd.field_item("clone", Item::Entity);
         vd.field_bool("get_state_from_parent");
         vd.field_numeric("scale");
@@ -260,7 +313,12 @@ impl Asset {
             vd.field_item("blend_shape", Item::BlendShape);
             vd.field_numeric("default");
         });
-        vd.multi_field_validated_block("meshsettings", validate_meshsettings);
+        vd.multi_field_validated_block("meshsettings", |block, data| {
+            validate_meshsettings(block, data);
+            if let Some((info, pdxmesh)) = &mesh {
+                validate_shape_name(block.get_field_value("name"), info, pdxmesh);
+            }
+        });
         vd.multi_field_validated_block("game_data", |block, data| {
             let mut vd = Validator::new(block, data);
             vd.multi_field_validated_block("portrait_entity_user_data", |block, data| {
@@ -341,8 +399,24 @@ impl Asset {
         });
         vd.multi_field_validated_block("attach", |block, data| {
             let mut vd = Validator::new(block, data);
-            vd.unknown_value_fields(|_, token| {
-                // TODO: what are the keys here?
+            vd.unknown_value_fields(|key, token| {
+                // The keys are the bones or locators to attach to
+                if let Some((info, pdxmesh)) = &mesh {
+                    let is_locator = self.block.get_field_blocks("locator").iter().any(|block| {
+                        block
+                            .get_field_value("name")
+                            .is_some_and(|name| name.lowercase_is(key.as_str()))
+                    });
+                    if !is_locator && !info.has_bone_or_locator(key.as_str()) {
+                        let msg = format!("no bone or locator `{key}` in the mesh of {pdxmesh}");
+                        warn(ErrorKey::MissingItem)
+                            .weak()
+                            .msg(msg)
+                            .loc(key)
+                            .loc_msg(*pdxmesh, "pdxmesh here")
+                            .push();
+                    }
+                }
                 data.verify_exists(Item::Asset, token);
             });
         });
@@ -359,8 +433,9 @@ impl Asset {
             vd.req_field("type");
             vd.field_value("id");
             if let Some(token) = vd.field_value("type") {
-                let path = self.key.loc.pathname().smart_join_parent(token.as_str());
+                let path = self.file_path(token);
                 data.verify_exists_implied(Item::File, &path.to_string_lossy(), token);
+                self.validate_binary(data, &path);
             }
         });
     }
@@ -380,6 +455,20 @@ impl Asset {
     }
 }
 
+/// Warn if `name` is not one of the shapes in the mesh file.
+fn validate_shape_name(name: Option<&Token>, info: &MeshInfo, file: &Token) {
+    if let Some(name) = name {
+        if !info.has_shape(name.as_str()) {
+            let msg = format!("no shape `{name}` in the mesh file for {file}");
+            warn(ErrorKey::MissingItem)
+                .msg(msg)
+                .loc(name)
+                .loc_msg(file, "mesh file from here")
+                .push();
+        }
+    }
+}
+
 fn validate_event(block: &Block, data: &Everything) {
     let mut vd = Validator::new(block, data);
     vd.field_numeric("time");
@@ -129,12 +129,16 @@ pub fn validate_coa_layout(block: &Block, data: &Everything) {
     let mut vd = Validator::new(block, data);
     vd.set_max_severity(Severity::Warning);
 
+    // The number of color channels in the pattern texture, which is how many areas
+    // an emblem mask can select from.
+    let mut pattern_channels = None;
     if let Some(token) = vd.field_value("pattern") {
         if let Some((_, token)) = token.split_once('"') {
             data.verify_exists(Item::CoaPatternList, &token);
         } else {
             let pathname = format!("gfx/coat_of_arms/patterns/{token}");
             data.verify_exists_implied(Item::File, &pathname, token);
+            pattern_channels = data.dds.color_channels(&pathname);
         }
     }
 
@@ -158,12 +162,14 @@ pub fn validate_coa_layout(block: &Block, data: &Everything) {
         let mut vd = Validator::new(subblock, data);
         vd.set_max_severity(Severity::Warning);
         vd.req_field("texture");
+        let mut texture_channels = None;
         if let Some(token) = vd.field_value("texture") {
             if let Some((_, token)) = token.split_once('"') {
                 data.verify_exists(Item::CoaColoredEmblemList, &token);
             } else {
                 let pathname = format!("gfx/coat_of_arms/colored_emblems/{token}");
                 data.verify_exists_implied(Item::File, &pathname, token);
+                texture_channels = data.dds.color_channels(&pathname);
             }
         }
 
@@ -172,6 +178,17 @@ pub fn validate_coa_layout(block: &Block, data: &Everything) {
                 validate_coa_color(bv, Some(block), data);
             });
         }
+        // The red, green and blue channels of the emblem texture are painted with color1 to color3.
+        if let Some(channels) = texture_channels {
+            for n in channels + 1..=3 {
+                if let Some(key) = subblock.get_key(&format!("color{n}")) {
+                    let msg = format!("color{n} is not used by this emblem texture");
+                    let info =
+                        format!("the texture's format only has {channels} of the 3 color channels");
+                    untidy(ErrorKey::Colors).msg(msg).info(info).loc(key).push();
+                }
+            }
+        }
         vd.multi_field_validated_block("instance", validate_instance);
         vd.field_validated_block("mask", |block, data| {
             let mut vd = Validator::new(block, data);
@@ -180,6 +197,14 @@ pub fn validate_coa_layout(block: &Block, data: &Everything) {
                 if let Some(mask) = token.expect_integer() {
                     if !(1..=3).contains(&mask) {
                         warn(ErrorKey::Range).msg("mask should be from 1 to 3").loc(token).push();
+                    } else if let Some(channels) = pattern_channels {
+                        if mask > i64::from(channels) {
+                            let msg = format!("mask {mask} does not select any part of the pattern");
+                            let info = format!(
+                                "the pattern texture's format only has {channels} of the 3 color channels"
+                            );
+                            warn(ErrorKey::Colors).msg(msg).info(info).loc(token).push();
+                        }
                     }
                 }
             }
@@ -350,7 +375,10 @@ where
 
     // TODO: warn about duplicate values in the lists?
 
-    vd.integer_keys(|_, bv| f(bv, data));
+    vd.integer_keys(|key, bv| {
+        validate_weight(key);
+        f(bv, data);
+    });
 
     vd.multi_field_validated_key_block("special_selection", |key, block, data| {
         let mut vd = Validator::new(block, data);
@@ -387,7 +415,10 @@ where
         vd.multi_field_validated_block("trigger", |block, data| {
             validate_trigger_max_sev(block, data, &mut sc, Tooltipped::No, Severity::Warning);
         });
-        vd.integer_keys(|_, bv| f(bv, data));
+        vd.integer_keys(|key, bv| {
+            validate_weight(key);
+            f(bv, data);
+        });
         // special_selection can be nested. TODO: how far?
         vd.multi_field_validated_block("special_selection", |block, data| {
             let mut vd = Validator::new(block, data);
@@ -395,11 +426,23 @@ where
             vd.multi_field_validated_block("trigger", |block, data| {
                 validate_trigger_max_sev(block, data, &mut sc, Tooltipped::No, Severity::Warning);
             });
-            vd.integer_keys(|_, bv| f(bv, data));
+            vd.integer_keys(|key, bv| {
+                validate_weight(key);
+                f(bv, data);
+            });
         });
     });
 }
 
+/// The keys of the entries in coa lists are their weights for random selection.
+fn validate_weight(key: &Token) {
+    if key.get_integer().is_some_and(|weight| weight <= 0) {
+        let msg = "weight should be positive";
+        let info = "this entry will never be picked";
+        warn(ErrorKey::Range).msg(msg).info(info).loc(key).push();
+    }
+}
+
 #[cfg(feature = "ck3")]
 #[derive(Clone, Debug)]
 pub struct CoaDynamicDefinition {}
@@ -442,6 +485,42 @@ fn validate_instance(block: &Block, data: &Everything) {
     vd.field_precise_numeric("rotation");
     vd.field_precise_numeric("depth");
     vd.ban_field("offset", || "sub blocks");
+    validate_instance_bounds(block);
+}
+
+/// Warn if an emblem instance is too small to see or lies entirely outside the shield.
+/// Positions go from 0 to 1 across the shield, and scales are relative to the shield's size.
+fn validate_instance_bounds(block: &Block) {
+    let numbers = |field| -> Option<Vec<f64>> {
+        let list = block.get_field_list(field)?;
+        list.iter().map(Token::get_number).collect()
+    };
+    let (x, y) = match numbers("position").as_deref() {
+        Some([x, y]) => (*x, *y),
+        Some(_) => return,
+        None => (0.5, 0.5),
+    };
+    let (sx, sy) = match numbers("scale").as_deref() {
+        Some([sx, sy]) => (sx.abs(), sy.abs()),
+        Some([sx]) => (sx.abs(), sx.abs()),
+        Some(_) => return,
+        None => (1.0, 1.0),
+    };
+
+    if sx == 0.0 || sy == 0.0 {
+        let msg = "emblem is scaled to nothing";
+        let key = block.get_key("scale").map_or(block.loc, |key| key.loc);
+        warn(ErrorKey::Range).msg(msg).loc(key).push();
+        return;
+    }
+    // Allow for any rotation by using the emblem's circumscribed circle
+    let radius = sx.hypot(sy) / 2.0;
+    if x + radius < 0.0 || x - radius > 1.0 || y + radius < 0.0 || y - radius > 1.0 {
+        let msg = "emblem is entirely outside the shield";
+        let info = "positions go from 0 to 1 across the shield";
+        let key = block.get_key("position").map_or(block.loc, |key| key.loc);
+        warn(ErrorKey::Range).msg(msg).info(info).loc(key).push();
+    }
 }
 
 /// Just like [`validate_instance`], but takes offset instead of position
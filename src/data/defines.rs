@@ -1,29 +1,64 @@
+use std::fmt::Write;
 use std::path::PathBuf;
 
 use crate::block::{Block, BV};
 use crate::everything::Everything;
-use crate::fileset::{FileEntry, FileHandler};
+use crate::fileset::{FileEntry, FileHandler, FileKind};
 use crate::helpers::{dup_error, TigerHashMap};
 use crate::parse::ParserMemory;
 use crate::pdxfile::PdxFile;
+use crate::report::{err, untidy, warn, ErrorKey};
 use crate::token::Token;
 
 #[derive(Clone, Debug, Default)]
+#[allow(clippy::struct_field_names)]
 pub struct Defines {
     defines: TigerHashMap<String, Define>,
+    /// The vanilla versions of defines that were overridden by a mod, including the ones from
+    /// vanilla files that a mod replaced entirely.
+    vanilla: TigerHashMap<String, Define>,
+    /// For each vanilla defines file that a mod replaced with its own copy, the keys it defines.
+    replaced: Vec<(PathBuf, Vec<String>)>,
 }
 
 impl Defines {
     pub fn load_item(&mut self, group: Token, name: Token, bv: &BV) {
         let key = format!("{}|{}", &group, &name);
-        if let Some(other) = self.defines.get(&key) {
+        if let Some(other) = self.defines.remove(&key) {
             if other.name.loc.kind >= name.loc.kind && !bv.equivalent(&other.bv) {
                 dup_error(&name, &other.name, "define");
             }
+            if other.name.loc.kind.counts_as_vanilla() && !name.loc.kind.counts_as_vanilla() {
+                self.vanilla.insert(key.clone(), other);
+            }
         }
         self.defines.insert(key, Define::new(group, name, bv.clone()));
     }
 
+    /// Remember the defines from a vanilla file that was replaced by a mod's file with the same
+    /// path, so that the mod's copy can be compared to it.
+    pub fn load_replaced_file(&mut self, entry: &FileEntry, parser: &ParserMemory) {
+        if !entry.kind().counts_as_vanilla()
+            || !entry.filename().to_string_lossy().ends_with(".txt")
+        {
+            return;
+        }
+        let Some(mut block) = PdxFile::read(entry, parser) else {
+            return;
+        };
+        let mut keys = Vec::new();
+        for (group, block) in block.drain_definitions_warn() {
+            for (name, bv) in block.iter_assignments_and_definitions_warn() {
+                let key = format!("{}|{}", &group, &name);
+                keys.push(key.clone());
+                self.vanilla
+                    .entry(key)
+                    .or_insert_with(|| Define::new(group.clone(), name.clone(), bv.clone()));
+            }
+        }
+        self.replaced.push((entry.path().to_path_buf(), keys));
+    }
+
     pub fn exists(&self, key: &str) -> bool {
         self.defines.contains_key(key)
     }
@@ -34,9 +69,78 @@ impl Defines {
     }
 
     pub fn validate(&self, data: &Everything) {
-        for item in self.defines.values() {
+        for (key, item) in &self.defines {
             item.validate(data);
+            if let Some(vanilla) = self.vanilla.get(key) {
+                let in_copy = self
+                    .replaced
+                    .iter()
+                    .any(|(path, _)| item.name.loc.pathname() == path.as_path());
+                item.validate_override(key, vanilla, in_copy);
+            }
+        }
+        self.check_replaced_files();
+    }
+
+    /// Warn about mod copies of vanilla defines files that no longer match the vanilla file,
+    /// which usually means the copy was made from an older version of the game.
+    fn check_replaced_files(&self) {
+        for (path, keys) in &self.replaced {
+            let mut copied: Vec<_> = self
+                .defines
+                .iter()
+                .filter(|(_, item)| item.name.loc.pathname() == path.as_path())
+                .collect();
+            let Some((_, first)) = copied.iter().min_by_key(|(_, item)| item.name.loc) else {
+                continue;
+            };
+
+            let mut missing: Vec<_> = keys
+                .iter()
+                .filter(|key| !self.defines.contains_key(*key))
+                .map(String::as_str)
+                .collect();
+            if !missing.is_empty() {
+                missing.sort_unstable();
+                let msg = format!(
+                    "this copy of {} is missing defines from the current vanilla file",
+                    path.display()
+                );
+                let info = format!("missing: {}", missing.join(", "));
+                warn(ErrorKey::Validation).msg(msg).info(info).loc(&first.name).push();
+            }
+
+            copied.retain(|(key, _)| !keys.contains(key));
+            copied.sort_unstable_by_key(|(_, item)| item.name.loc);
+            for (key, item) in copied {
+                let msg = format!("define `{key}` is not in the current vanilla file");
+                let info = "it may have been removed or renamed in a game update";
+                warn(ErrorKey::Validation).msg(msg).info(info).loc(&item.name).push();
+            }
+        }
+    }
+
+    /// Describe all the defines that the mods change or add, one per line, with their vanilla
+    /// and new values. Meant for patch notes.
+    pub fn diff(&self) -> String {
+        let mut changed: Vec<_> = self
+            .defines
+            .iter()
+            .filter(|(_, item)| !item.name.loc.kind.counts_as_vanilla())
+            .filter(|(key, item)| {
+                self.vanilla.get(*key).map_or(true, |vanilla| !item.bv.equivalent(&vanilla.bv))
+            })
+            .collect();
+        changed.sort_unstable_by_key(|(key, _)| key.as_str());
+        let mut out = String::new();
+        for (key, item) in changed {
+            if let Some(vanilla) = self.vanilla.get(key) {
+                _ = writeln!(out, "{key}: {} -> {}", describe(&vanilla.bv), describe(&item.bv));
+            } else {
+                _ = writeln!(out, "{key}: (new) {}", describe(&item.bv));
+            }
         }
+        out
     }
 
     #[cfg(feature = "ck3")] // vic3 happens not to use
@@ -85,4 +189,87 @@ impl Define {
         // TODO: validate that each define is the right 'type',
         // such as a path, a number, or a block of numeric values
     }
+
+    /// Compare a mod's define with the vanilla define it overrides.
+    /// `in_copy` means the define is in a full copy of a vanilla file, where it has to be present
+    /// even if it has the vanilla value.
+    fn validate_override(&self, key: &str, vanilla: &Define, in_copy: bool) {
+        let kind = DefineType::of(&self.bv);
+        let vanilla_kind = DefineType::of(&vanilla.bv);
+        if kind != vanilla_kind {
+            let msg = format!(
+                "define `{key}` is {} here but {} in vanilla",
+                kind.describe(),
+                vanilla_kind.describe()
+            );
+            err(ErrorKey::Validation)
+                .msg(msg)
+                .loc(&self.name)
+                .loc_msg(&vanilla.name, "vanilla define")
+                .push();
+        } else if let (Some(block), Some(vanilla_block)) =
+            (self.bv.get_block(), vanilla.bv.get_block())
+        {
+            let len = block.iter_items().len();
+            let vanilla_len = vanilla_block.iter_items().len();
+            if len != vanilla_len {
+                let msg =
+                    format!("define `{key}` has {len} values here but {vanilla_len} in vanilla");
+                warn(ErrorKey::Validation)
+                    .msg(msg)
+                    .loc(&self.name)
+                    .loc_msg(&vanilla.name, "vanilla define")
+                    .push();
+            }
+        }
+        if !in_copy && self.name.loc.kind == FileKind::Mod && self.bv.equivalent(&vanilla.bv) {
+            let msg = format!("define `{key}` is set to its vanilla value");
+            untidy(ErrorKey::Unneeded)
+                .msg(msg)
+                .loc(&self.name)
+                .loc_msg(&vanilla.name, "vanilla define")
+                .push();
+        }
+    }
+}
+
+/// The broad type of a define's value. A mod that changes it is likely to break the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DefineType {
+    Number,
+    String,
+    Array,
+}
+
+impl DefineType {
+    fn of(bv: &BV) -> Self {
+        match bv {
+            BV::Value(token) if token.is_number() => DefineType::Number,
+            BV::Value(_) => DefineType::String,
+            BV::Block(_) => DefineType::Array,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            DefineType::Number => "a number",
+            DefineType::String => "a string",
+            DefineType::Array => "an array",
+        }
+    }
+}
+
+/// A short form of a define's value, for the diff.
+fn describe(bv: &BV) -> String {
+    match bv {
+        BV::Value(token) => token.to_string(),
+        BV::Block(block) => {
+            let mut s = "{".to_owned();
+            for value in block.iter_values() {
+                _ = write!(s, " {value}");
+            }
+            s.push_str(" }");
+            s
+        }
+    }
 }
@@ -20,6 +20,40 @@ impl Ethnicity {
     pub fn add(db: &mut Db, key: Token, block: Block) {
         db.add(Item::Ethnicity, key, block, Box::new(Self {}));
     }
+
+    /// Return the ethnicities that give characters some templates of gene `category`, but never
+    /// `template`. Genes that an ethnicity doesn't list are taken from the ethnicity it is based on.
+    pub fn without_template<'a>(
+        category: &str,
+        template: &str,
+        data: &'a Everything,
+    ) -> Vec<&'a Token> {
+        let mut found = Vec::new();
+        for (key, block) in data.database.iter_key_block(Item::Ethnicity) {
+            let mut block = block;
+            let mut depth = 0;
+            let gene = loop {
+                if let Some(gene) = block.get_field_block(category) {
+                    break Some(gene);
+                }
+                let base = block.get_field_value("template");
+                match base.and_then(|base| data.get_key_block(Item::Ethnicity, base.as_str())) {
+                    Some((_, base)) if depth < 16 => block = base,
+                    _ => break None,
+                }
+                depth += 1;
+            };
+            let Some(gene) = gene else {
+                continue;
+            };
+            let mut names =
+                gene.iter_definitions().filter_map(|(_, b)| b.get_field_value("name")).peekable();
+            if names.peek().is_some() && !names.any(|name| name.is(template)) {
+                found.push(key);
+            }
+        }
+        found
+    }
 }
 
 impl DbKind for Ethnicity {
@@ -1,10 +1,12 @@
 use crate::block::Block;
 use crate::db::{Db, DbKind};
 use crate::everything::Everything;
+use crate::fileset::FileKind;
+use crate::font::CharMap;
 use crate::game::GameFlags;
 use crate::item::{Item, ItemLoader};
 use crate::pdxfile::PdxEncoding;
-use crate::report::{untidy, warn, ErrorKey, Severity};
+use crate::report::{err, untidy, warn, ErrorKey, Severity};
 use crate::token::Token;
 use crate::validator::Validator;
 
@@ -78,6 +80,76 @@ impl DbKind for Fontfiles {
             let mut vd = Validator::new(block, data);
             vd.field_list("languages"); // TODO
             vd.field_list_items("files", Item::File);
+            if block.loc.kind >= FileKind::LoadedMod(0) {
+                check_coverage(block, data);
+            }
         });
     }
 }
+
+/// Characters that are not drawn with a glyph, so the font doesn't need to have them.
+fn is_invisible(ch: char) -> bool {
+    ch.is_control()
+        || ch.is_whitespace()
+        || matches!(ch, '\u{200b}'..='\u{200f}' | '\u{2060}' | '\u{feff}')
+}
+
+/// Check that the font files of a `group` have glyphs for all the characters that the mod's
+/// localization uses in the group's languages.
+fn check_coverage(block: &Block, data: &Everything) {
+    let Some(files) = block.get_field_list("files") else {
+        return;
+    };
+    let mut charmap = CharMap::default();
+    for file in &files {
+        // Missing files are already reported by the validator
+        let Some(entry) = data.fileset.get_entry(file.as_str()) else {
+            return;
+        };
+        match CharMap::read(entry.fullpath()) {
+            Ok(map) => charmap.extend(&map),
+            Err(e) => {
+                let msg = "could not read character map of font file";
+                let info = format!("{e:#}");
+                err(ErrorKey::ReadError).msg(msg).info(info).loc(file).push();
+                return;
+            }
+        }
+    }
+
+    let languages = block.get_field_list("languages");
+    for lang in data.localization.mod_langs() {
+        if let Some(languages) = &languages {
+            if !languages
+                .iter()
+                .any(|l| l.as_str().strip_prefix("l_").unwrap_or(l.as_str()) == *lang)
+            {
+                continue;
+            }
+        }
+        let missing: Vec<_> = data
+            .localization
+            .mod_chars(lang)
+            .into_iter()
+            .filter(|(ch, _)| !is_invisible(*ch) && !charmap.covers(*ch))
+            .collect();
+        if let Some((_, key)) = missing.first() {
+            let msg = format!(
+                "font files have no glyphs for {} characters used in the {lang} localization",
+                missing.len()
+            );
+            let mut info: Vec<String> =
+                missing.iter().take(20).map(|(ch, _)| ch.to_string()).collect();
+            if missing.len() > 20 {
+                info.push("...".to_owned());
+            }
+            let info = format!("missing: {}", info.join(" "));
+            warn(ErrorKey::MissingGlyph)
+                .msg(msg)
+                .info(info)
+                .loc(block)
+                .loc_msg(*key, "first used here")
+                .push();
+        }
+    }
+}
@@ -127,6 +127,11 @@ impl DbKind for ColorGene {
     ) {
         let mut vd = Validator::new(call_block, data);
         vd.req_tokens_numbers_exactly(4);
+        for token in call_block.iter_values() {
+            if token.get_number().is_some_and(|v| !(0.0..=255.0).contains(&v)) {
+                warn(ErrorKey::Range).msg("expected value from 0 to 255").loc(token).push();
+            }
+        }
     }
 }
 
@@ -201,8 +206,9 @@ impl DbKind for MorphGene {
                 fatal(ErrorKey::Crash).msg(msg).loc(token).push();
             }
         }
-        vd.unknown_block_fields(|_, block| {
+        vd.unknown_block_fields(|key, block| {
             validate_morph_gene(block, data);
+            validate_body_types(key, block);
         });
     }
 
@@ -244,8 +250,8 @@ impl DbKind for MorphGene {
                     err(ErrorKey::MissingItem).msg(msg).loc(token).push();
                 }
             } else if let Some(i) = token.expect_integer() {
-                if !(0..=256).contains(&i) {
-                    warn(ErrorKey::Range).msg("expected value from 0 to 256").loc(token).push();
+                if !(0..=255).contains(&i) {
+                    warn(ErrorKey::Range).msg("expected value from 0 to 255").loc(token).push();
                 }
             }
             count += 1;
@@ -369,8 +375,9 @@ impl DbKind for AccessoryGene {
             vd.field_integer("index");
         }
 
-        vd.unknown_block_fields(|_, block| {
+        vd.unknown_block_fields(|key, block| {
             validate_accessory_gene(block, data);
+            validate_body_types(key, block);
         });
     }
 
@@ -412,8 +419,8 @@ impl DbKind for AccessoryGene {
                     err(ErrorKey::MissingItem).msg(msg).loc(token).push();
                 }
             } else if let Some(i) = token.expect_integer() {
-                if !(0..=256).contains(&i) {
-                    warn(ErrorKey::Range).msg("expected value from 0 to 256").loc(token).push();
+                if !(0..=255).contains(&i) {
+                    warn(ErrorKey::Range).msg("expected value from 0 to 255").loc(token).push();
                 }
             }
             count += 1;
@@ -573,6 +580,49 @@ fn validate_morph_gene(block: &Block, data: &Everything) {
     }
 }
 
+/// Check that every body type of a gene template ends up at a block of settings, possibly by
+/// referring to another body type such as `girl = female`. Characters of a body type that has
+/// nothing get no accessory or morph from this gene, which makes them bald or faceless.
+fn validate_body_types(template: &Token, block: &Block) {
+    #[cfg(any(feature = "ck3", feature = "vic3"))]
+    let choices = &["male", "female", "boy", "girl"];
+    #[cfg(feature = "imperator")]
+    let choices = &["male", "female", "boy", "girl", "infant"];
+
+    for field in ["male", "female", "boy", "girl"] {
+        let mut current = field;
+        let mut seen = vec![field];
+        loop {
+            match block.get_field(current) {
+                Some(BV::Block(_)) => break,
+                // Unknown body types are reported elsewhere
+                Some(BV::Value(token)) if !choices.contains(&token.as_str()) => break,
+                Some(BV::Value(token)) => {
+                    current = token.as_str();
+                    if seen.contains(&current) {
+                        let msg = format!(
+                            "{field} in gene template {template} refers to itself in a loop"
+                        );
+                        warn(ErrorKey::Loop).msg(msg).loc(token).push();
+                        break;
+                    }
+                    seen.push(current);
+                }
+                None => {
+                    let msg = if current == field {
+                        format!("gene template {template} has no entry for {field}")
+                    } else {
+                        format!("{field} in gene template {template} refers to missing {current}")
+                    };
+                    let info = format!("{field} characters will get nothing from this gene");
+                    warn(ErrorKey::FieldMissing).msg(msg).info(info).loc(template).push();
+                    break;
+                }
+            }
+        }
+    }
+}
+
 fn validate_accessory_gene(block: &Block, data: &Everything) {
     let mut vd = Validator::new(block, data);
     vd.req_field("index");
@@ -1,12 +1,15 @@
 //! Validate files in `gui/`
 
+use std::fmt::Write as _;
 use std::mem::drop;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
+use anyhow::{bail, Result};
+
 use crate::block::{Block, BlockItem, Field, BV};
 use crate::everything::Everything;
-use crate::fileset::{FileEntry, FileHandler};
+use crate::fileset::{FileEntry, FileHandler, FileKind};
 use crate::gui::{BuiltinWidget, GuiBlock, GuiBlockFrom};
 use crate::helpers::{dup_error, TigerHashMap, TigerHashSet};
 use crate::item::Item;
@@ -31,6 +34,11 @@ pub struct Gui {
     // This is indexed by a (colorblindmode, textformatname) pair
     textformats_colorblind: TigerHashMap<(&'static str, &'static str), TextFormat>,
     widget_names: TigerHashSet<Token>,
+    // Names of the blocks targeted by `blockoverride`s in the base game's gui files
+    vanilla_overrides: TigerHashSet<&'static str>,
+    // Names of the blocks in the base game's versions of types that the mod replaces,
+    // indexed by lowercase type name
+    vanilla_type_blocks: TigerHashMap<Lowercase<'static>, Vec<Token>>,
 }
 
 impl Gui {
@@ -138,11 +146,54 @@ impl Gui {
         if let Some(other) = self.types.get(&key_lc) {
             if other.key.loc.kind >= key.loc.kind {
                 dup_error(&key, &other.key, "gui type");
+            } else if other.key.loc.kind < FileKind::LoadedMod(0) {
+                let mut names = Vec::new();
+                collect_named_blocks(&other.block, "block", &mut names);
+                let names = names.into_iter().cloned();
+                self.vanilla_type_blocks.entry(key_lc.clone()).or_default().extend(names);
             }
         }
         self.types.insert(key_lc, GuiType::new(key, base, block));
     }
 
+    /// Remember the blocks in the widget types of a base game gui file that was replaced by a mod
+    /// file with the same name. This is used to check that the mod's versions of those types
+    /// still have the blocks that the base game's `blockoverride`s apply to.
+    pub fn load_replaced_file(&mut self, entry: &FileEntry, parser: &ParserMemory) {
+        if entry.kind() >= FileKind::LoadedMod(0)
+            || !entry.filename().to_string_lossy().ends_with(".gui")
+        {
+            return;
+        }
+        let Some(block) = PdxFile::read_optional_bom(entry, parser) else {
+            return;
+        };
+        for types in block.iter_items().as_slice().windows(3) {
+            let [BlockItem::Value(kw), BlockItem::Value(_), BlockItem::Block(body)] = types else {
+                continue;
+            };
+            if !kw.lowercase_is("types") {
+                continue;
+            }
+            for def in body.iter_items().as_slice().windows(3) {
+                let [BlockItem::Value(kw), BlockItem::Field(Field(key, _, _)), BlockItem::Block(body)] =
+                    def
+                else {
+                    continue;
+                };
+                if kw.lowercase_is("type") || kw.lowercase_is("local_type") {
+                    let mut names = Vec::new();
+                    collect_named_blocks(body, "block", &mut names);
+                    let key_lc = Lowercase::new(key.as_str());
+                    self.vanilla_type_blocks
+                        .entry(key_lc)
+                        .or_default()
+                        .extend(names.into_iter().cloned());
+                }
+            }
+        }
+    }
+
     pub fn load_template(&mut self, key: Token, block: Block) {
         if let Some(other) = self.templates.get(key.as_str()) {
             if other.key.loc.kind >= key.loc.kind {
@@ -238,6 +289,51 @@ impl Gui {
         self.widget_names.iter()
     }
 
+    /// Return the fully resolved contents of the top-level widgets with the given `name`, and of
+    /// the widget type with that name if there is one, as `.gui` script.
+    /// See [`GuiBlock::dump`].
+    pub fn dump(&self, name: &str) -> Result<String> {
+        let mut out = String::new();
+
+        let mut paths: Vec<&PathBuf> = self.files.keys().collect();
+        paths.sort();
+        for path in paths {
+            for widget in &self.files[path] {
+                if !widget.block.get_field_value("name").is_some_and(|n| n.is(name)) {
+                    continue;
+                }
+                let guiblock = GuiBlock::from_block(
+                    GuiBlockFrom::WidgetKey(&widget.key),
+                    &widget.block,
+                    &self.types,
+                    &self.templates,
+                );
+                _ = writeln!(
+                    out,
+                    "# {}:{}",
+                    widget.key.loc.pathname().display(),
+                    widget.key.loc.line
+                );
+                _ = writeln!(out, "{} = {{", widget.key);
+                guiblock.dump(1, &mut out);
+                _ = writeln!(out, "}}\n");
+            }
+        }
+
+        if let Some(item) = self.types.get(&Lowercase::new(name)) {
+            let guiblock = item.gui_block(&self.types, &self.templates);
+            _ = writeln!(out, "# {}:{}", item.key.loc.pathname().display(), item.key.loc.line);
+            _ = writeln!(out, "type {} = {} {{", item.key, item.base);
+            guiblock.dump(1, &mut out);
+            _ = writeln!(out, "}}\n");
+        }
+
+        if out.is_empty() {
+            bail!("no top-level widget or widget type named {name}");
+        }
+        Ok(out)
+    }
+
     pub fn validate(&self, data: &Everything) {
         for items in self.files.values() {
             for item in items {
@@ -292,6 +388,12 @@ impl FileHandler<Block> for Gui {
             LayerBody(Token),
         }
 
+        if entry.kind() < FileKind::LoadedMod(0) {
+            let mut names = Vec::new();
+            collect_named_blocks(&block, "blockoverride", &mut names);
+            self.vanilla_overrides.extend(names.iter().map(|name| name.as_str()));
+        }
+
         let mut expecting = Expecting::Widget;
 
         for item in block.drain() {
@@ -581,7 +683,34 @@ impl GuiType {
                 .push();
         }
         // Unwrapping the Option is safe because they were all calculated during finalize
-        self.gui_block.read().unwrap().as_ref().unwrap().validate(None, data);
+        let gui_block = Arc::clone(self.gui_block.read().unwrap().as_ref().unwrap());
+        gui_block.validate(None, data);
+
+        if self.key.loc.kind < FileKind::LoadedMod(0) {
+            return;
+        }
+        let Some(vanilla_blocks) =
+            data.gui.vanilla_type_blocks.get(&Lowercase::new(self.key.as_str()))
+        else {
+            return;
+        };
+        let mut warned = TigerHashSet::default();
+        for name in vanilla_blocks {
+            if data.gui.vanilla_overrides.contains(name.as_str())
+                && !gui_block.has_block(name.as_str())
+                && warned.insert(name.as_str())
+            {
+                let msg = format!("this type replaces a base game type but has no block `{name}`");
+                let info =
+                    "blockoverrides for it in the base game's gui files will no longer apply";
+                warn(ErrorKey::Gui)
+                    .msg(msg)
+                    .info(info)
+                    .loc(&self.key)
+                    .loc_msg(name, "block in base game type")
+                    .push();
+            }
+        }
     }
 
     pub fn calculate_builtin(
@@ -677,3 +806,33 @@ impl GuiLayer {
         vd.field_value("raycast_layer");
     }
 }
+
+/// Find the names of all `keyword "name" { ... }` constructs in `block` and its sub-blocks,
+/// such as `block "name"` or `blockoverride "name"`.
+fn collect_named_blocks<'a>(block: &'a Block, keyword: &str, names: &mut Vec<&'a Token>) {
+    let mut expect_name = false;
+    for item in block.iter_items() {
+        match item {
+            BlockItem::Value(token) => {
+                if expect_name {
+                    names.push(token);
+                    expect_name = false;
+                } else {
+                    expect_name = token.lowercase_is(keyword);
+                }
+            }
+            BlockItem::Field(Field(key, _, bv)) => {
+                expect_name = false;
+                match bv {
+                    BV::Value(token) if key.lowercase_is(keyword) => names.push(token),
+                    BV::Value(_) => (),
+                    BV::Block(block) => collect_named_blocks(block, keyword, names),
+                }
+            }
+            BlockItem::Block(block) => {
+                expect_name = false;
+                collect_named_blocks(block, keyword, names);
+            }
+        }
+    }
+}
@@ -2,17 +2,22 @@
 
 use std::borrow::Borrow;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::ffi::OsStr;
-use std::fs::read_to_string;
+use std::fmt::{Display, Formatter, Write as _};
+use std::fs::{create_dir_all, read_to_string, OpenOptions};
 #[cfg(any(feature = "ck3", feature = "vic3"))]
 use std::io::Cursor;
+use std::io::Write as _;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering::Relaxed;
 
+use anyhow::{anyhow, bail, Context, Result};
 #[cfg(any(feature = "ck3", feature = "vic3"))]
 use murmur3::murmur3_32;
 use rayon::scope;
+use unicode_width::UnicodeWidthStr;
 
 use crate::block::Block;
 #[cfg(feature = "ck3")]
@@ -22,7 +27,8 @@ use crate::datatype::{validate_datatypes, CodeChain, Datatype};
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler, FileKind};
 use crate::game::Game;
-use crate::helpers::{dup_error, stringify_list, TigerHashMap};
+use crate::game_data::GameData;
+use crate::helpers::{dup_error, stringify_list, TigerHashMap, TigerHashSet};
 #[cfg(feature = "imperator")]
 use crate::imperator::tables::localization::BUILTIN_MACROS_IMPERATOR;
 use crate::item::Item;
@@ -48,9 +54,71 @@ pub struct Localization {
     mod_langs: Vec<&'static str>,
     /// Database of all localizations, indexed first by language and then by localization key.
     locas: TigerHashMap<&'static str, TigerHashMap<String, LocaEntry>>,
+    /// Text length limits for localization keys, according to the config file.
+    limits: Vec<LocaLimit>,
+    /// Whether to report mixed scripts and look-alike characters, according to the config file.
+    check_mixed_script: bool,
+}
+
+/// A configured limit on the displayed size of localization values whose keys match a pattern.
+/// Used to catch translations that will overflow the fixed-size widgets they are shown in.
+#[derive(Debug, Default)]
+struct LocaLimit {
+    prefix: Option<String>,
+    suffix: Option<String>,
+    /// If set, the limit only applies to this language.
+    language: Option<&'static str>,
+    /// Maximum number of terminal-style columns in any one line of the text.
+    max_width: Option<usize>,
+    /// Maximum number of characters in the whole text.
+    max_length: Option<usize>,
+}
+
+impl LocaLimit {
+    fn from_block(block: &Block) -> Option<Self> {
+        let mut limit = LocaLimit {
+            prefix: block.get_field_value("prefix").map(Token::to_string),
+            suffix: block.get_field_value("suffix").map(Token::to_string),
+            ..Default::default()
+        };
+        if let Some(lang) = block.get_field_value("language") {
+            if let Ok(lang) = known_language(lang.as_str()) {
+                limit.language = Some(lang);
+            } else {
+                let msg = format!("unknown language `{lang}`");
+                err(ErrorKey::Config).msg(msg).loc(lang).push();
+                return None;
+            }
+        }
+        for (field, value) in
+            [("max_width", &mut limit.max_width), ("max_length", &mut limit.max_length)]
+        {
+            if let Some(token) = block.get_field_value(field) {
+                if let Ok(n) = token.as_str().parse() {
+                    *value = Some(n);
+                } else {
+                    let msg = format!("expected a whole number for `{field}`");
+                    err(ErrorKey::Config).msg(msg).loc(token).push();
+                }
+            }
+        }
+        if limit.max_width.is_none() && limit.max_length.is_none() {
+            let msg = "limit should have `max_width` or `max_length`";
+            warn(ErrorKey::Config).msg(msg).loc(block).push();
+            return None;
+        }
+        Some(limit)
+    }
+
+    fn applies(&self, key: &str, lang: &str) -> bool {
+        self.language.map_or(true, |l| l == lang)
+            && self.prefix.as_ref().map_or(true, |p| key.starts_with(p.as_str()))
+            && self.suffix.as_ref().map_or(true, |s| key.ends_with(s.as_str()))
+    }
 }
 
 /// List of languages that are supported by the game engine.
+/// The game data from `--game-data` replaces this list if it includes the game's languages.
 // LAST UPDATED CK3 VERSION 1.14.0.2
 // LAST UPDATED VIC3 VERSION 1.7.6
 pub const KNOWN_LANGUAGES: &[&str] = &[
@@ -194,9 +262,9 @@ pub enum LocaValue {
     // at those.
     Macro(Vec<MacroValue>),
     Concat(Vec<LocaValue>),
-    #[allow(dead_code)] // the Token is only used for ck3
     Text(Token),
-    Markup,
+    // The token is the markup text itself, such as `#bold`
+    Markup(Token),
     MarkupEnd,
     Tooltip(Token),
     // Tag, key, value. Tag can influence how tooltip is looked up. If tag is `GAME_TRAIT`,
@@ -227,10 +295,85 @@ fn get_file_lang(filename: &OsStr) -> Option<&'static str> {
     // Using to_string_lossy is ok here because non-unicode sequences will
     // never match the suffix anyway.
     let filename = filename.to_string_lossy();
-    KNOWN_LANGUAGES.iter().find(|&lang| filename.contains(&format!("l_{lang}"))).copied()
+    known_languages().into_iter().find(|&lang| filename.contains(&format!("l_{lang}")))
+}
+
+/// Return the languages supported by the game engine. They are taken from the game data if it
+/// lists them, and from [`KNOWN_LANGUAGES`] otherwise.
+fn known_languages() -> Vec<&'static str> {
+    match GameData::get().map(GameData::languages) {
+        Some(langs) if !langs.is_empty() => langs.iter().map(String::as_str).collect(),
+        _ => Vec::from(KNOWN_LANGUAGES),
+    }
+}
+
+/// Look up `lang` in [`known_languages`], to get a `'static` version of it.
+fn known_language(lang: &str) -> Result<&'static str> {
+    let known_languages = known_languages();
+    known_languages.iter().find(|&&known| known == lang).copied().ok_or_else(|| {
+        anyhow!("unknown language {lang}, expected one of {}", known_languages.join(", "))
+    })
+}
+
+/// Take the path of a localization file in language `from`, and return the path where the
+/// corresponding file for language `to` should go.
+///
+/// Both the `l_language` part of the filename and a directory named after the language are
+/// converted. Returns `None` if the resulting filename would not be recognized as language `to`.
+fn sync_path(path: &Path, from: &str, to: &'static str) -> Option<PathBuf> {
+    let mut result = PathBuf::new();
+    for component in path.parent()? {
+        if component == from {
+            result.push(to);
+        } else {
+            result.push(component);
+        }
+    }
+    let filename =
+        path.file_name()?.to_string_lossy().replacen(&format!("l_{from}"), &format!("l_{to}"), 1);
+    if get_file_lang(OsStr::new(&filename)) != Some(to) {
+        return None;
+    }
+    result.push(filename);
+    Some(result)
 }
 
 impl Localization {
+    pub fn mod_langs(&self) -> &[&'static str] {
+        &self.mod_langs
+    }
+
+    /// Return the characters displayed by the mod's localization for `lang`, each with the key
+    /// of the first entry that uses it.
+    pub fn mod_chars(&self, lang: &str) -> Vec<(char, &Token)> {
+        let Some(hash) = self.locas.get(lang) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<&LocaEntry> =
+            hash.values().filter(|e| e.key.loc.kind >= FileKind::LoadedMod(0)).collect();
+        entries.sort_unstable();
+        let mut seen = TigerHashSet::default();
+        let mut chars = Vec::new();
+        for entry in entries {
+            let mut text = String::new();
+            let mut width = 0;
+            collect_text(&entry.value, &mut text, &mut width);
+            for ch in text.chars() {
+                if seen.insert(ch) {
+                    chars.push((ch, &entry.key));
+                }
+            }
+        }
+        chars
+    }
+
+    /// Return the unparsed text of a localization key, in the first of the mod's languages that
+    /// has it.
+    #[cfg(any(feature = "ck3", feature = "vic3"))]
+    pub fn get_text(&self, key: &str) -> Option<&Token> {
+        self.mod_langs.iter().find_map(|lang| self.locas.get(lang)?.get(key)?.orig.as_ref())
+    }
+
     pub fn exists(&self, key: &str) -> bool {
         for lang in &self.mod_langs {
             let hash = self.locas.get(lang);
@@ -536,6 +679,44 @@ impl Localization {
         }
     }
 
+    fn check_text(&self, lang: &str, hash: &TigerHashMap<String, LocaEntry>) {
+        let mut entries: Vec<&LocaEntry> =
+            hash.values().filter(|e| e.key.loc.kind >= FileKind::LoadedMod(0)).collect();
+        entries.sort_unstable();
+        for entry in entries {
+            // Macros are only filled in at runtime, so their length is unknown
+            if matches!(entry.value, LocaValue::Macro(_)) {
+                continue;
+            }
+            let mut text = String::new();
+            let mut width = 0;
+            collect_text(&entry.value, &mut text, &mut width);
+            for limit in &self.limits {
+                if !limit.applies(entry.key.as_str(), lang) {
+                    continue;
+                }
+                let length = text.chars().count();
+                if let Some(max) = limit.max_length.filter(|&max| length > max) {
+                    let msg = format!("{lang} text is {length} characters long, limit is {max}");
+                    let info = "it may not fit in the widgets that display it";
+                    warn(ErrorKey::LocalizationLength).msg(msg).info(info).loc(&entry.key).push();
+                }
+                let line_width = text.split('\n').map(UnicodeWidthStr::width).max().unwrap_or(0);
+                if let Some(max) = limit.max_width.filter(|&max| line_width + width > max) {
+                    let msg = format!(
+                        "{lang} text is {} columns wide, limit is {max}",
+                        line_width + width
+                    );
+                    let info = "it may not fit in the widgets that display it";
+                    warn(ErrorKey::LocalizationLength).msg(msg).info(info).loc(&entry.key).push();
+                }
+            }
+            if self.check_mixed_script {
+                check_mixed_script(&entry.value);
+            }
+        }
+    }
+
     // This is in pass2 to make sure all `validated` entries have been marked.
     pub fn validate_pass2(&self, data: &Everything) {
         scope(|s| {
@@ -544,6 +725,9 @@ impl Localization {
                 #[cfg(any(feature = "ck3", feature = "vic3"))]
                 s.spawn(|_| self.check_collisions(lang));
 
+                // Check the displayed text for overflow and look-alike letters
+                s.spawn(|_| self.check_text(lang, hash));
+
                 // Collect and sort the entries before looping, to create more stable output
                 let mut unvalidated_entries: Vec<&LocaEntry> =
                     hash.values().filter(|e| !e.validated.load(Relaxed)).collect();
@@ -606,6 +790,200 @@ impl Localization {
         }
     }
 
+    /// Copy the mod's localization entries from the `from` language to all the `langs` languages
+    /// that don't have them yet.
+    ///
+    /// The entries are appended to the per-language files that correspond to the files the
+    /// entries came from, which are created (with BOM and language header) if needed.
+    /// Existing content of those files is left alone. If `marker` is not empty, it is added
+    /// as a comment after each copied entry, so that translators can find them.
+    ///
+    /// Returns the pathnames of the files that were written, and how many entries went into each.
+    pub fn sync(
+        &self,
+        mod_root: &Path,
+        from: &str,
+        langs: &[&str],
+        marker: &str,
+    ) -> Result<Vec<(PathBuf, usize)>> {
+        let mut known_langs = Vec::new();
+        for lang in langs {
+            let lang = known_language(lang)?;
+            // Languages that aren't checked weren't loaded, so every key would look missing.
+            if !self.check_langs.contains(&lang) {
+                bail!("language {lang} is not checked according to the config file");
+            }
+            known_langs.push(lang);
+        }
+        let Some(hash) = self.locas.get(from) else {
+            return Ok(Vec::new());
+        };
+        let mut entries: Vec<&LocaEntry> =
+            hash.values().filter(|e| e.key.loc.kind == FileKind::Mod && e.orig.is_some()).collect();
+        entries.sort_unstable();
+
+        let mut result = Vec::new();
+        for lang in known_langs {
+            if lang == from {
+                continue;
+            }
+            let mut files: BTreeMap<PathBuf, Vec<&LocaEntry>> = BTreeMap::new();
+            for entry in &entries {
+                if self.exists_lang(entry.key.as_str(), lang) {
+                    continue;
+                }
+                if let Some(path) = sync_path(entry.key.loc.pathname(), from, lang) {
+                    files.entry(path).or_default().push(entry);
+                }
+            }
+            for (path, entries) in files {
+                let fullpath = mod_root.join(&path);
+                let mut content = String::new();
+                if let Ok(existing) = read_to_string(&fullpath) {
+                    if !existing.is_empty() && !existing.ends_with('\n') {
+                        content.push('\n');
+                    }
+                } else {
+                    if let Some(parent) = fullpath.parent() {
+                        create_dir_all(parent)
+                            .with_context(|| format!("could not create {}", parent.display()))?;
+                    }
+                    content.push('\u{feff}');
+                    _ = writeln!(content, "l_{lang}:");
+                }
+                for entry in &entries {
+                    // unwrap is safe because of the filter on `orig` above
+                    let orig = entry.orig.as_ref().unwrap();
+                    _ = write!(content, " {}: \"{orig}\"", entry.key);
+                    if !marker.is_empty() {
+                        _ = write!(content, " # {marker}");
+                    }
+                    content.push('\n');
+                }
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&fullpath)
+                    .and_then(|mut f| f.write_all(content.as_bytes()))
+                    .with_context(|| format!("could not write {}", fullpath.display()))?;
+                result.push((path, entries.len()));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Render the localization `key` in language `lang` as plain text, for previewing.
+    ///
+    /// `$macros$` are expanded, `[code]` is shown together with the datatype it returns,
+    /// `#markup` is shown as `<markup>` and `#!` as `</>`, and icons are shown as `<icon:name>`.
+    ///
+    /// `scopes` are the scope types of the named scopes that the localization is used with.
+    /// The name `root` sets the root scope.
+    pub fn render(
+        &self,
+        key: &str,
+        lang: &str,
+        scopes: &[(&str, Scopes)],
+        data: &Everything,
+    ) -> Result<String> {
+        let lang = known_language(lang)?;
+        let Some(hash) = self.locas.get(lang) else {
+            bail!("no {lang} localization is loaded");
+        };
+        let Some(entry) = hash.get(key) else {
+            bail!("missing {lang} localization key {key}");
+        };
+
+        let mut sc = ScopeContext::new_unrooted(Scopes::all(), &entry.key);
+        sc.set_strict_scopes(false);
+        for &(name, scopes) in scopes {
+            if name.eq_ignore_ascii_case("root") {
+                sc = ScopeContext::new(scopes, &entry.key);
+                sc.set_strict_scopes(false);
+            }
+        }
+        for &(name, scopes) in scopes {
+            if !name.eq_ignore_ascii_case("root") {
+                sc.define_name(name, scopes, &entry.key);
+            }
+        }
+
+        let value = if matches!(entry.value, LocaValue::Macro(_)) {
+            let mut new_line = Vec::new();
+            let mut count = 0;
+            if !entry.expand_macros(&mut new_line, hash, &mut count, &mut sc, None)
+                || new_line.is_empty()
+            {
+                bail!("could not expand the macros in {key}");
+            }
+            ValueParser::new(new_line.iter().collect()).parse()
+        } else {
+            entry.value.clone()
+        };
+
+        let mut rendered = String::new();
+        Self::render_value(&value, data, &mut sc, lang, &mut rendered);
+        Ok(rendered)
+    }
+
+    fn render_value(
+        value: &LocaValue,
+        data: &Everything,
+        sc: &mut ScopeContext,
+        lang: &'static str,
+        rendered: &mut String,
+    ) {
+        match value {
+            LocaValue::Concat(v) => {
+                for value in v {
+                    Self::render_value(value, data, sc, lang, rendered);
+                }
+            }
+            LocaValue::Macro(v) => {
+                for macrovalue in v {
+                    match macrovalue {
+                        MacroValue::Text(token) => rendered.push_str(token.as_str()),
+                        MacroValue::Keyword(token) => _ = write!(rendered, "${token}$"),
+                    }
+                }
+            }
+            LocaValue::Text(token) => rendered.push_str(token.as_str()),
+            LocaValue::Markup(token) => {
+                let markup = token.as_str().strip_prefix('#').unwrap_or(token.as_str());
+                _ = write!(rendered, "<{markup}>");
+            }
+            LocaValue::MarkupEnd => rendered.push_str("</>"),
+            // These are part of a markup, which is already shown
+            LocaValue::Tooltip(_) | LocaValue::ComplexTooltip(_, _) => (),
+            LocaValue::Code(chain, format) => {
+                _ = write!(rendered, "[{chain}");
+                if let Some(format) = format {
+                    _ = write!(rendered, "|{format}");
+                }
+                #[cfg(feature = "ck3")]
+                if Game::is_ck3()
+                    && format.as_ref().is_some_and(|f| f.as_str().contains(['E', 'e']))
+                    && chain.as_gameconcept().is_some()
+                {
+                    rendered.push_str(" => game concept]");
+                    return;
+                }
+                let dtype = validate_datatypes(
+                    chain,
+                    data,
+                    sc,
+                    Datatype::Unknown,
+                    lang,
+                    format.as_ref(),
+                    false,
+                );
+                _ = write!(rendered, " => {dtype}]");
+            }
+            LocaValue::Icon(token) => _ = write!(rendered, "<icon:{token}>"),
+            LocaValue::Error => rendered.push_str("<error>"),
+        }
+    }
+
     #[cfg(feature = "ck3")]
     pub fn check_pod_loca(&self, data: &Everything) {
         for lang in &self.mod_langs {
@@ -638,7 +1016,7 @@ impl FileHandler<(&'static str, Vec<LocaEntry>)> for Localization {
             // TODO: warn if there are unknown languages in check or skip?
             let check = block.get_field_values("check");
             let skip = block.get_field_values("skip");
-            for lang in KNOWN_LANGUAGES {
+            for lang in known_languages() {
                 if check.iter().any(|t| t.is(lang))
                     || (check.is_empty() && skip.iter().all(|t| !t.is(lang)))
                 {
@@ -647,6 +1025,13 @@ impl FileHandler<(&'static str, Vec<LocaEntry>)> for Localization {
             }
             self.check_langs = langs;
         }
+
+        if let Some(block) = config.get_field_block("localization_limits") {
+            for block in block.get_field_blocks("limit") {
+                self.limits.extend(LocaLimit::from_block(block));
+            }
+            self.check_mixed_script = block.field_value_is("check_mixed_script", "yes");
+        }
     }
 
     fn subpath(&self) -> PathBuf {
@@ -681,7 +1066,7 @@ impl FileHandler<(&'static str, Vec<LocaEntry>)> for Localization {
             // Localization files don't have to be in a subdirectory corresponding to their language.
             // However, if there's one in a subdirectory for a *different* language than the one in its name,
             // then something is probably wrong.
-            if filelang != lang && KNOWN_LANGUAGES.contains(&&*lang) {
+            if filelang != lang && known_languages().contains(&&*lang) {
                 let msg = "localization file with wrong name or in wrong directory";
                 let info = "A localization file should be in a subdirectory corresponding to its language.";
                 warn(ErrorKey::Filename).msg(msg).info(info).loc(entry).push();
@@ -701,7 +1086,7 @@ impl FileHandler<(&'static str, Vec<LocaEntry>)> for Localization {
             let msg = "could not determine language from filename";
             let info = format!(
                 "Localization filenames should end in _l_language.yml, where language is one of {}",
-                KNOWN_LANGUAGES.join(", ")
+                known_languages().join(", ")
             );
             err(ErrorKey::Filename).msg(msg).info(info).loc(entry).push();
         }
@@ -711,7 +1096,7 @@ impl FileHandler<(&'static str, Vec<LocaEntry>)> for Localization {
     fn handle_file(&mut self, entry: &FileEntry, loaded: (&'static str, Vec<LocaEntry>)) {
         let (filelang, mut vec) = loaded;
         if entry.kind() == FileKind::Mod && !self.mod_langs.contains(&filelang) {
-            for &known in KNOWN_LANGUAGES {
+            for known in known_languages() {
                 if known == filelang {
                     self.mod_langs.push(known);
                 }
@@ -738,9 +1123,11 @@ impl FileHandler<(&'static str, Vec<LocaEntry>)> for Localization {
 impl Default for Localization {
     fn default() -> Self {
         Localization {
-            check_langs: Vec::from(KNOWN_LANGUAGES),
+            check_langs: known_languages(),
             locas: TigerHashMap::default(),
             mod_langs: Vec::default(),
+            limits: Vec::default(),
+            check_mixed_script: false,
         }
     }
 }
@@ -755,6 +1142,88 @@ fn is_replace_path(path: &Path) -> bool {
     false
 }
 
+/// Gather the plain text of a loca value, as it would be displayed.
+/// Icons are not text but take up room, so their width is added to `icon_width` instead.
+/// Code and markup are skipped because their displayed size can't be known here.
+fn collect_text(value: &LocaValue, text: &mut String, icon_width: &mut usize) {
+    match value {
+        LocaValue::Concat(v) => {
+            for value in v {
+                collect_text(value, text, icon_width);
+            }
+        }
+        LocaValue::Text(token) => text.push_str(token.as_str()),
+        LocaValue::Icon(_) => *icon_width += 2,
+        _ => (),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+}
+
+impl Script {
+    fn of(ch: char) -> Option<Self> {
+        match ch {
+            'a'..='z' | 'A'..='Z' | '\u{c0}'..='\u{24f}' if ch.is_alphabetic() => {
+                Some(Script::Latin)
+            }
+            '\u{370}'..='\u{3ff}' if ch.is_alphabetic() => Some(Script::Greek),
+            '\u{400}'..='\u{52f}' if ch.is_alphabetic() => Some(Script::Cyrillic),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Script {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Script::Latin => write!(f, "Latin"),
+            Script::Greek => write!(f, "Greek"),
+            Script::Cyrillic => write!(f, "Cyrillic"),
+        }
+    }
+}
+
+/// Warn about words that mix letters from different scripts, such as a Latin `o` in a Cyrillic word.
+/// These are usually look-alike letters that crept in from a keyboard layout switch,
+/// and they break searching and sorting and may not be in the font.
+fn check_mixed_script(value: &LocaValue) {
+    match value {
+        LocaValue::Concat(v) => {
+            for value in v {
+                check_mixed_script(value);
+            }
+        }
+        LocaValue::Text(token) => {
+            for word in token.as_str().split(|c: char| !c.is_alphabetic()) {
+                let mut counts: Vec<(Script, usize)> = Vec::new();
+                for script in word.chars().filter_map(Script::of) {
+                    if let Some((_, n)) = counts.iter_mut().find(|(s, _)| *s == script) {
+                        *n += 1;
+                    } else {
+                        counts.push((script, 1));
+                    }
+                }
+                if counts.len() < 2 {
+                    continue;
+                }
+                counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+                let main = counts[0].0;
+                let odd: String =
+                    word.chars().filter(|&c| Script::of(c).is_some_and(|s| s != main)).collect();
+                let msg = format!("word `{word}` mixes {main} letters with `{odd}`");
+                let info = format!("`{odd}` may look like {main} but is from another script");
+                warn(ErrorKey::MixedScript).msg(msg).info(info).loc(token).push();
+            }
+        }
+        _ => (),
+    }
+}
+
 /// These are the languages in which it's reasonable to present an ascii name unchanged.
 #[cfg(feature = "ck3")]
 const LATIN_SCRIPT_LANGS: &[&str] =
@@ -784,6 +1253,16 @@ fn normal_capitalization_for_name(name: &str) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_script_of() {
+        assert_eq!(Script::of('o'), Some(Script::Latin));
+        assert_eq!(Script::of('é'), Some(Script::Latin));
+        assert_eq!(Script::of('о'), Some(Script::Cyrillic));
+        assert_eq!(Script::of('ο'), Some(Script::Greek));
+        assert_eq!(Script::of('1'), None);
+        assert_eq!(Script::of('×'), None);
+    }
+
     #[test]
     fn test_only_latin_script() {
         let mut langs = vec!["english", "french", "german"];
@@ -794,6 +1273,22 @@ mod tests {
         assert!(only_latin_script(&langs));
     }
 
+    #[test]
+    fn test_sync_path() {
+        let path = Path::new("localization/english/my_events_l_english.yml");
+        assert_eq!(
+            sync_path(path, "english", "german"),
+            Some(PathBuf::from("localization/german/my_events_l_german.yml"))
+        );
+        let path = Path::new("localization/replace/my_l_english.yml");
+        assert_eq!(
+            sync_path(path, "english", "french"),
+            Some(PathBuf::from("localization/replace/my_l_french.yml"))
+        );
+        let path = Path::new("localization/english/no_language.yml");
+        assert_eq!(sync_path(path, "english", "german"), None);
+    }
+
     #[test]
     fn test_normal_capitalization_for_name() {
         assert!(normal_capitalization_for_name("George"));
@@ -43,6 +43,10 @@ impl OnActions {
         self.on_actions.values().map(|item| &item.actions[0].0)
     }
 
+    pub fn get(&self, key: &str) -> Option<&OnAction> {
+        self.on_actions.get(key)
+    }
+
     pub fn validate(&self, data: &Everything) {
         for item in self.on_actions.values() {
             item.validate(data);
@@ -99,6 +103,11 @@ impl OnAction {
         self.actions.push((key, block));
     }
 
+    /// Iterate over all the definitions of this `on_action`, in load order.
+    pub fn iter_definitions(&self) -> impl Iterator<Item = (&Token, &Block)> {
+        self.actions.iter().map(|(key, block)| (key, block))
+    }
+
     pub fn validate(&self, data: &Everything) {
         let mut seen_trigger = false;
         let mut seen_effect = false;
@@ -1,5 +1,6 @@
 use crate::block::{Block, BV};
 use crate::context::ScopeContext;
+use crate::data::ethnicity::Ethnicity;
 use crate::data::genes::{AccessoryGene, Gene};
 use crate::db::{Db, DbKind};
 use crate::everything::Everything;
@@ -359,9 +360,19 @@ pub fn validate_dna_modifiers(block: &Block, data: &Everything) {
         if let Some(category) = block.get_field_value("gene") {
             if let Some(template) = vd.field_value("template") {
                 Gene::verify_has_template(category.as_str(), template, data);
+                validate_template_for_ethnicities(block, category, template, data);
             }
         }
         vd.field_script_value_full("value", Scopes::Character, false);
+        // `modify_multiply` scales the existing value, so factors above 1 are fine.
+        if let Some(token) = block.get_field_value("value") {
+            if !block.field_value_is("mode", "modify_multiply")
+                && token.get_number().is_some_and(|v| !(-1.0..=1.0).contains(&v))
+            {
+                let msg = "morph values go from -1 to 1";
+                warn(ErrorKey::Range).msg(msg).loc(token).push();
+            }
+        }
         vd.field_validated_block("range", |block, data| {
             validate_numeric_range(block, data, 0.0, 1.0, Severity::Warning, Confidence::Weak);
         });
@@ -380,6 +391,7 @@ pub fn validate_dna_modifiers(block: &Block, data: &Everything) {
         if let Some(category) = block.get_field_value("gene") {
             if let Some(template) = vd.field_value("template") {
                 Gene::verify_has_template(category.as_str(), template, data);
+                validate_template_for_ethnicities(block, category, template, data);
             }
         }
         vd.field_script_value_full("value", Scopes::Character, false);
@@ -388,5 +400,69 @@ pub fn validate_dna_modifiers(block: &Block, data: &Everything) {
         });
         vd.field_item("accessory", Item::Accessory);
         vd.field_choice("type", &["male", "female", "boy", "girl"]);
+        validate_accessory_in_template(block, data);
     });
 }
+
+/// Check that a dna modifier that modifies the value of a gene template is not meant for a
+/// template that some ethnicities never give to characters. The modifier does nothing for
+/// characters of those ethnicities, because they don't have the template.
+fn validate_template_for_ethnicities(
+    block: &Block,
+    category: &Token,
+    template: &Token,
+    data: &Everything,
+) {
+    let Some(mode) = block.get_field_value("mode") else {
+        return;
+    };
+    if !(mode.is("modify") || mode.is("modify_multiply")) {
+        return;
+    }
+    let mut missing = Ethnicity::without_template(category.as_str(), template.as_str(), data);
+    if missing.is_empty() {
+        return;
+    }
+    missing.sort_unstable_by_key(|key| key.as_str());
+    let count = missing.len();
+    let mut names: Vec<_> = missing.iter().take(5).map(|key| key.as_str()).collect();
+    if count > 5 {
+        names.push("...");
+    }
+    let msg =
+        format!("{count} ethnicities never give characters template {template} of gene {category}");
+    let info = format!(
+        "this modifier has no effect on characters of those ethnicities: {}",
+        names.join(", ")
+    );
+    warn(ErrorKey::Validation).weak().msg(msg).info(info).loc(template).push();
+}
+
+/// Check that an accessory set by a dna modifier is one of the choices of the gene template it
+/// is set for. The game can't show accessories from outside the template.
+fn validate_accessory_in_template(block: &Block, data: &Everything) {
+    let Some(gene) = block.get_field_value("gene") else {
+        return;
+    };
+    let Some(template) = block.get_field_value("template") else {
+        return;
+    };
+    let Some(accessory) = block.get_field_value("accessory") else {
+        return;
+    };
+    if let Some((key, gene_block)) = data.get_key_block(Item::GeneCategory, gene.as_str()) {
+        if gene_block.get_field_block(template.as_str()).is_some()
+            && !AccessoryGene::has_template_setting(
+                key,
+                gene_block,
+                data,
+                template.as_str(),
+                accessory.as_str(),
+            )
+        {
+            let msg = format!("accessory {accessory} is not used in gene template {template}");
+            let info = "characters with this modifier may be shown without the accessory";
+            warn(ErrorKey::MissingItem).weak().msg(msg).info(info).loc(accessory).push();
+        }
+    }
+}
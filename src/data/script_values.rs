@@ -44,6 +44,11 @@ impl ScriptValues {
         self.script_values.contains_key(key)
     }
 
+    /// Return the definition of a script value, for evaluating it.
+    pub fn get_bv(&self, key: &str) -> Option<&BV> {
+        self.script_values.get(key).map(|item| &item.bv)
+    }
+
     pub fn iter_keys(&self) -> impl Iterator<Item = &Token> {
         self.script_values.values().map(|item| &item.key)
     }
@@ -1,13 +1,14 @@
+use std::borrow::Cow;
 use std::fmt::Debug;
 use std::path::PathBuf;
 
-use crate::block::Block;
+use crate::block::{Block, BV};
 use crate::context::ScopeContext;
 use crate::effect::validate_effect;
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::{dup_error, exact_dup_error, TigerHashMap, BANNED_NAMES};
-use crate::macros::{MacroCache, MACRO_MAP};
+use crate::macros::{CallRecord, MacroCache, MACRO_MAP};
 use crate::parse::ParserMemory;
 use crate::pdxfile::PdxFile;
 use crate::report::{err, warn, ErrorKey};
@@ -61,6 +62,13 @@ impl Effects {
             item.validate(data);
         }
     }
+
+    /// Compare the call sites of each scripted effect with each other. Must be called after validation.
+    pub fn check_calls(&self) {
+        for item in self.effects.values() {
+            item.calls.check(&item.key, "scripted effect");
+        }
+    }
 }
 
 impl FileHandler<Block> for Effects {
@@ -110,11 +118,18 @@ pub struct Effect {
     block: Block,
     cache: MacroCache<ScopeContext>,
     scope_override: Option<Scopes>,
+    calls: CallRecord,
 }
 
 impl Effect {
     pub fn new(key: Token, block: Block, scope_override: Option<Scopes>) -> Self {
-        Self { key, block, cache: MacroCache::default(), scope_override }
+        Self {
+            key,
+            block,
+            cache: MacroCache::default(),
+            scope_override,
+            calls: CallRecord::default(),
+        }
     }
 
     pub fn validate(&self, data: &Everything) {
@@ -156,6 +171,31 @@ impl Effect {
         self.block.macro_parms()
     }
 
+    /// Remember how this is called at `key`, for [`CallRecord::check`].
+    pub fn record_call(
+        &self,
+        key: &Token,
+        bv: &BV,
+        args: &[(&'static str, Token)],
+        sc: &mut ScopeContext,
+    ) {
+        self.calls.record(key, bv, args, sc);
+    }
+
+    /// Return the body of this scripted effect, with the macro arguments of a call filled in.
+    pub fn body(
+        &self,
+        key: &Token,
+        args: &[(&'static str, Token)],
+        data: &Everything,
+    ) -> Option<Cow<'_, Block>> {
+        if args.is_empty() {
+            Some(Cow::Borrowed(&self.block))
+        } else {
+            self.block.expand_macro(args, key.loc, &data.parser).map(Cow::Owned)
+        }
+    }
+
     pub fn cached_compat(
         &self,
         key: &Token,
@@ -179,7 +219,7 @@ impl Effect {
         // Every invocation is treated as different even if the args are the same,
         // because we want to point to the correct one when reporting errors.
         if !self.cached_compat(key, args, tooltipped, sc) {
-            if let Some(block) = self.block.expand_macro(args, key.loc, &data.parser.pdxfile) {
+            if let Some(block) = self.block.expand_macro(args, key.loc, &data.parser) {
                 let mut our_sc = ScopeContext::new_unrooted(Scopes::all(), &self.key);
                 our_sc.set_strict_scopes(false);
                 if self.scope_override.is_some() {
@@ -136,7 +136,7 @@ impl ScriptedModifier {
         // Every invocation is treated as different even if the args are the same,
         // because we want to point to the correct one when reporting errors.
         if !self.cached_compat(key, args, sc) {
-            if let Some(block) = self.block.expand_macro(args, key.loc, &data.parser.pdxfile) {
+            if let Some(block) = self.block.expand_macro(args, key.loc, &data.parser) {
                 let mut our_sc = ScopeContext::new_unrooted(Scopes::all(), &self.key);
                 our_sc.set_strict_scopes(false);
                 // Insert the dummy sc before continuing. That way, if we recurse, we'll hit
@@ -1,12 +1,13 @@
+use std::borrow::Cow;
 use std::path::PathBuf;
 
-use crate::block::Block;
+use crate::block::{Block, BV};
 use crate::context::ScopeContext;
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::{dup_error, exact_dup_error, TigerHashMap, BANNED_NAMES};
 use crate::lowercase::Lowercase;
-use crate::macros::{MacroCache, MACRO_MAP};
+use crate::macros::{CallRecord, MacroCache, MACRO_MAP};
 use crate::parse::ParserMemory;
 use crate::pdxfile::PdxFile;
 use crate::report::{err, warn, ErrorKey, Severity};
@@ -65,6 +66,13 @@ impl Triggers {
             item.validate(data);
         }
     }
+
+    /// Compare the call sites of each scripted trigger with each other. Must be called after validation.
+    pub fn check_calls(&self) {
+        for item in self.triggers.values() {
+            item.calls.check(&item.key, "scripted trigger");
+        }
+    }
 }
 
 impl FileHandler<Block> for Triggers {
@@ -114,11 +122,18 @@ pub struct Trigger {
     block: Block,
     cache: MacroCache<ScopeContext>,
     scope_override: Option<Scopes>,
+    calls: CallRecord,
 }
 
 impl Trigger {
     pub fn new(key: Token, block: Block, scope_override: Option<Scopes>) -> Self {
-        Self { key, block, cache: MacroCache::default(), scope_override }
+        Self {
+            key,
+            block,
+            cache: MacroCache::default(),
+            scope_override,
+            calls: CallRecord::default(),
+        }
     }
 
     pub fn validate(&self, data: &Everything) {
@@ -172,6 +187,31 @@ impl Trigger {
         self.block.macro_parms()
     }
 
+    /// Remember how this is called at `key`, for [`CallRecord::check`].
+    pub fn record_call(
+        &self,
+        key: &Token,
+        bv: &BV,
+        args: &[(&'static str, Token)],
+        sc: &mut ScopeContext,
+    ) {
+        self.calls.record(key, bv, args, sc);
+    }
+
+    /// Return the body of this scripted trigger, with the macro arguments of a call filled in.
+    pub fn body(
+        &self,
+        key: &Token,
+        args: &[(&'static str, Token)],
+        data: &Everything,
+    ) -> Option<Cow<'_, Block>> {
+        if args.is_empty() {
+            Some(Cow::Borrowed(&self.block))
+        } else {
+            self.block.expand_macro(args, key.loc, &data.parser).map(Cow::Owned)
+        }
+    }
+
     pub fn cached_compat(
         &self,
         key: &Token,
@@ -197,7 +237,7 @@ impl Trigger {
         // Every invocation is treated as different even if the args are the same,
         // because we want to point to the correct one when reporting errors.
         if !self.cached_compat(key, args, tooltipped, negated, sc) {
-            if let Some(block) = self.block.expand_macro(args, key.loc, &data.parser.pdxfile) {
+            if let Some(block) = self.block.expand_macro(args, key.loc, &data.parser) {
                 let mut our_sc = ScopeContext::new_unrooted(Scopes::all(), &self.key);
                 our_sc.set_strict_scopes(false);
                 if self.scope_override.is_some() {
@@ -16,6 +16,17 @@ impl Date {
     pub fn new(year: i16, month: i8, day: i8) -> Self {
         Date { year, month, day }
     }
+
+    /// Return the number of full years from this date until `date`,
+    /// such as the age on `date` of someone born on this date.
+    pub fn years_until(self, date: Date) -> i16 {
+        let years = date.year - self.year;
+        if (date.month, date.day) < (self.month, self.day) {
+            years - 1
+        } else {
+            years
+        }
+    }
 }
 
 impl FromStr for Date {
@@ -59,3 +70,21 @@ impl Display for Date {
         write!(f, "{}.{}.{}", self.year, self.month, self.day)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_years_until() {
+        let birth = Date::new(1000, 6, 15);
+        assert_eq!(birth.years_until(Date::new(1000, 6, 15)), 0);
+        assert_eq!(birth.years_until(Date::new(1014, 6, 14)), 13);
+        assert_eq!(birth.years_until(Date::new(1014, 6, 15)), 14);
+        assert_eq!(birth.years_until(Date::new(1014, 5, 30)), 13);
+        assert_eq!(birth.years_until(Date::new(1014, 7, 1)), 14);
+        assert_eq!(birth.years_until(Date::new(1014, 12, 31)), 14);
+        assert_eq!(birth.years_until(Date::new(1015, 1, 1)), 14);
+        assert_eq!(birth.years_until(Date::new(1000, 1, 1)), -1);
+    }
+}
@@ -1,19 +1,50 @@
 //! Validator for the `.dds` (picture) files that are used in the game.
 
 use std::fs::{metadata, File};
-use std::io::{Read, Result};
+use std::io::{ErrorKind, Read, Result};
 use std::path::PathBuf;
 
-use crate::fileset::{FileEntry, FileHandler};
+use crate::fileset::{FileEntry, FileHandler, FileKind};
 use crate::helpers::TigerHashMap;
 use crate::parse::ParserMemory;
 use crate::report::{err, tips, warn, ErrorKey};
+use crate::token::Loc;
 #[cfg(feature = "ck3")]
 use crate::token::Token;
 
-const DDS_HEADER_SIZE: usize = 124;
+/// The size of the `DDS ` magic number plus the DDS header.
+const DDS_HEADER_SIZE: usize = 128;
+/// The part of the header that has to be present. The header ends in an unused field, which
+/// some tools leave off.
+const DDS_MIN_HEADER_SIZE: usize = 124;
+/// The size of the extra header that follows the DDS header if the format is `DX10`.
+const DX10_HEADER_SIZE: usize = 20;
+
+const DDS_FLAGS_OFFSET: usize = 8;
 const DDS_HEIGHT_OFFSET: usize = 12;
 const DDS_WIDTH_OFFSET: usize = 16;
+const DDS_MIPMAPCOUNT_OFFSET: usize = 28;
+const DDS_PIXELFORMAT_FLAGS_OFFSET: usize = 80;
+const DDS_FOURCC_OFFSET: usize = 84;
+const DDS_RGBBITCOUNT_OFFSET: usize = 88;
+const DDS_CAPS2_OFFSET: usize = 112;
+const DX10_FORMAT_OFFSET: usize = 128;
+const DX10_MISCFLAG_OFFSET: usize = 136;
+const DX10_ARRAYSIZE_OFFSET: usize = 140;
+
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_ALPHA: u32 = 0x2;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_PALETTEINDEXED8: u32 = 0x20;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x20000;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_CUBEMAP_ALLFACES: u32 = 0xFC00;
+const DX10_MISC_TEXTURECUBE: u32 = 0x4;
+
+/// A mod's texture is reported if it's this many times larger than the base game's version.
+const SIZE_RATIO_LIMIT: u64 = 4;
 
 fn from_le32(buffer: &[u8], offset: usize) -> u32 {
     u32::from(buffer[offset])
@@ -22,6 +53,27 @@ fn from_le32(buffer: &[u8], offset: usize) -> u32 {
         | (u32::from(buffer[offset + 3]) << 24)
 }
 
+/// Read the start of the file, including the `DX10` header if there is one.
+/// Returns the buffer and the number of header bytes that were read.
+fn read_header(entry: &FileEntry) -> Result<([u8; DDS_HEADER_SIZE + DX10_HEADER_SIZE], usize)> {
+    let mut f = File::open(entry.fullpath())?;
+    let mut buffer = [0; DDS_HEADER_SIZE + DX10_HEADER_SIZE];
+    let mut start = Vec::with_capacity(DDS_HEADER_SIZE);
+    f.by_ref().take(DDS_HEADER_SIZE as u64).read_to_end(&mut start)?;
+    if start.len() < DDS_MIN_HEADER_SIZE {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    buffer[..start.len()].copy_from_slice(&start);
+    let has_dx10 = buffer.starts_with(b"DDS ")
+        && from_le32(&buffer, DDS_PIXELFORMAT_FLAGS_OFFSET) & DDPF_FOURCC != 0
+        && &buffer[DDS_FOURCC_OFFSET..DDS_FOURCC_OFFSET + 4] == b"DX10";
+    if has_dx10 {
+        f.read_exact(&mut buffer[DDS_HEADER_SIZE..])?;
+        return Ok((buffer, DDS_HEADER_SIZE + DX10_HEADER_SIZE));
+    }
+    Ok((buffer, start.len()))
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct DdsFiles {
     dds_files: TigerHashMap<String, DdsInfo>,
@@ -29,13 +81,12 @@ pub struct DdsFiles {
 
 impl DdsFiles {
     fn load_dds(entry: &FileEntry) -> Result<Option<DdsInfo>> {
-        if metadata(entry.fullpath())?.len() == 0 {
+        let file_size = metadata(entry.fullpath())?.len();
+        if file_size == 0 {
             warn(ErrorKey::ImageFormat).msg("empty file").loc(entry).push();
             return Ok(None);
         }
-        let mut f = File::open(entry.fullpath())?;
-        let mut buffer = [0; DDS_HEADER_SIZE];
-        f.read_exact(&mut buffer)?;
+        let (buffer, header_size) = read_header(entry)?;
         if buffer.starts_with(b"\x89PNG") {
             let msg = "actually a PNG";
             let info =
@@ -47,13 +98,38 @@ impl DdsFiles {
             err(ErrorKey::ImageFormat).msg("not a DDS file").loc(entry).push();
             return Ok(None);
         }
-        Ok(Some(DdsInfo::new(&buffer)))
+        let info = DdsInfo::new(&buffer, header_size, file_size, entry.into());
+        info.validate(entry);
+        Ok(Some(info))
     }
 
     fn handle_dds(&mut self, entry: &FileEntry, info: DdsInfo) {
         self.dds_files.insert(entry.path().to_string_lossy().to_string(), info);
     }
 
+    /// Compare a base game texture that was replaced by a mod file with the same name against
+    /// the mod's version.
+    pub fn load_replaced_file(&self, entry: &FileEntry) {
+        if entry.kind() >= FileKind::LoadedMod(0)
+            || !entry.filename().to_string_lossy().ends_with(".dds")
+        {
+            return;
+        }
+        let Some(info) = self.dds_files.get(&*entry.path().to_string_lossy()) else {
+            return;
+        };
+        // Problems with the base game's file itself are not interesting here.
+        if let Ok(Some(vanilla)) = DdsInfo::load_quietly(entry) {
+            info.compare_vanilla(&vanilla);
+        }
+    }
+
+    /// Return the number of color channels of the texture at `path`, if it is known.
+    /// For example, BC4 textures only have a red channel.
+    pub fn color_channels(&self, path: &str) -> Option<u32> {
+        self.dds_files.get(path)?.channels
+    }
+
     #[cfg(feature = "ck3")]
     pub fn validate_frame(&self, key: &Token, width: u32, height: u32, frame: u32) {
         // Note: `frame` is 1-based
@@ -100,18 +176,379 @@ impl FileHandler<DdsInfo> for DdsFiles {
     }
 }
 
+/// The pixel format of a texture, as far as it matters for validation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum DdsFormat {
+    /// Block compressed, with the BC number from 1 to 7.
+    Compressed(u8),
+    /// Uncompressed, with the number of bits per pixel.
+    Uncompressed(u32),
+    /// A format that the game can't load, with a description of it.
+    Unsupported(&'static str),
+    /// A format that tiger doesn't recognize.
+    Unknown,
+}
+
+impl DdsFormat {
+    fn from_fourcc(fourcc: &[u8]) -> Self {
+        match fourcc {
+            b"DXT1" => DdsFormat::Compressed(1),
+            b"DXT2" | b"DXT3" => DdsFormat::Compressed(2),
+            b"DXT4" | b"DXT5" => DdsFormat::Compressed(3),
+            b"ATI1" | b"BC4U" | b"BC4S" => DdsFormat::Compressed(4),
+            b"ATI2" | b"BC5U" | b"BC5S" => DdsFormat::Compressed(5),
+            // Some formats are stored as a D3DFORMAT number instead of a character code
+            _ => match from_le32(fourcc, 0) {
+                111 => DdsFormat::Uncompressed(16),
+                112 | 114 => DdsFormat::Uncompressed(32),
+                36 | 110 | 113 | 115 => DdsFormat::Uncompressed(64),
+                116 => DdsFormat::Uncompressed(128),
+                _ => DdsFormat::Unknown,
+            },
+        }
+    }
+
+    fn from_dxgi(format: u32) -> Self {
+        match format {
+            1..=4 => DdsFormat::Uncompressed(128),
+            5..=8 => DdsFormat::Uncompressed(96),
+            9..=22 => DdsFormat::Uncompressed(64),
+            23..=47 | 67..=69 | 87..=93 => DdsFormat::Uncompressed(32),
+            48..=59 | 85 | 86 | 115 => DdsFormat::Uncompressed(16),
+            60..=65 => DdsFormat::Uncompressed(8),
+            70..=72 => DdsFormat::Compressed(1),
+            73..=75 => DdsFormat::Compressed(2),
+            76..=78 => DdsFormat::Compressed(3),
+            79..=81 => DdsFormat::Compressed(4),
+            82..=84 => DdsFormat::Compressed(5),
+            94..=96 => DdsFormat::Compressed(6),
+            97..=99 => DdsFormat::Compressed(7),
+            // Video formats such as YUV
+            100..=132 => DdsFormat::Unsupported("video format"),
+            _ => DdsFormat::Unknown,
+        }
+    }
+
+    /// The number of bytes needed for an image of this size, if known.
+    fn data_size(self, width: u32, height: u32) -> Option<u64> {
+        let (width, height) = (u64::from(width), u64::from(height));
+        match self {
+            DdsFormat::Compressed(bc) => {
+                let block_size = if bc == 1 || bc == 4 { 8 } else { 16 };
+                Some(width.div_ceil(4).max(1) * height.div_ceil(4).max(1) * block_size)
+            }
+            DdsFormat::Uncompressed(bits) => Some((width * height * u64::from(bits)).div_ceil(8)),
+            DdsFormat::Unsupported(_) | DdsFormat::Unknown => None,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct DdsInfo {
-    #[allow(dead_code)] // vic3 doesn't use
     width: u32,
-    #[allow(dead_code)] // vic3 doesn't use
     height: u32,
+    format: DdsFormat,
+    /// Whether the format is explicitly sRGB (gamma corrected) or linear.
+    /// It's `None` for files without a `DX10` header, which leave that up to the engine.
+    srgb: Option<bool>,
+    /// The number of color channels, if known from the format.
+    channels: Option<u32>,
+    /// The number of mipmap levels, including the full size image.
+    mipmaps: u32,
+    /// The number of cube faces (0 if not a cubemap) times the number of array elements.
+    faces: u32,
+    cubemap: bool,
+    header_size: usize,
+    file_size: u64,
+    loc: Loc,
 }
 
 impl DdsInfo {
-    pub fn new(header: &[u8]) -> Self {
+    /// `header_size` is the number of header bytes in the file, including the `DX10` header if
+    /// there is one.
+    fn new(header: &[u8], header_size: usize, file_size: u64, loc: Loc) -> Self {
+        let has_dx10 = header_size > DDS_HEADER_SIZE;
         let height = from_le32(header, DDS_HEIGHT_OFFSET);
         let width = from_le32(header, DDS_WIDTH_OFFSET);
-        Self { width, height }
+        let mipmaps = if from_le32(header, DDS_FLAGS_OFFSET) & DDSD_MIPMAPCOUNT != 0 {
+            from_le32(header, DDS_MIPMAPCOUNT_OFFSET).max(1)
+        } else {
+            1
+        };
+
+        let pf_flags = from_le32(header, DDS_PIXELFORMAT_FLAGS_OFFSET);
+        let caps2 = from_le32(header, DDS_CAPS2_OFFSET);
+        let mut srgb = None;
+        let mut channels = None;
+        let mut cubemap = caps2 & DDSCAPS2_CUBEMAP != 0;
+        let mut faces = if cubemap { (caps2 & DDSCAPS2_CUBEMAP_ALLFACES).count_ones() } else { 1 };
+        let format = if has_dx10 {
+            let dxgi = from_le32(header, DX10_FORMAT_OFFSET);
+            srgb = Some(matches!(dxgi, 29 | 72 | 75 | 78 | 91 | 93 | 99));
+            channels = match dxgi {
+                39..=43 | 53..=64 | 79..=81 => Some(1),
+                15..=18 | 33..=38 | 48..=52 | 82..=84 => Some(2),
+                1..=14 | 23..=32 | 67..=78 | 87..=99 => Some(3),
+                _ => None,
+            };
+            let array_size = from_le32(header, DX10_ARRAYSIZE_OFFSET).max(1);
+            if from_le32(header, DX10_MISCFLAG_OFFSET) & DX10_MISC_TEXTURECUBE != 0 {
+                cubemap = true;
+                faces = 6 * array_size;
+            } else {
+                faces = array_size;
+            }
+            DdsFormat::from_dxgi(dxgi)
+        } else if pf_flags & DDPF_FOURCC != 0 {
+            DdsFormat::from_fourcc(&header[DDS_FOURCC_OFFSET..DDS_FOURCC_OFFSET + 4])
+        } else if pf_flags & DDPF_PALETTEINDEXED8 != 0 {
+            DdsFormat::Unsupported("palettized color")
+        } else if pf_flags & (DDPF_RGB | DDPF_LUMINANCE | DDPF_ALPHA | DDPF_ALPHAPIXELS) != 0 {
+            match from_le32(header, DDS_RGBBITCOUNT_OFFSET) {
+                24 => DdsFormat::Unsupported("24-bit RGB"),
+                bits @ (8 | 16 | 32 | 64) => DdsFormat::Uncompressed(bits),
+                _ => DdsFormat::Unknown,
+            }
+        } else {
+            DdsFormat::Unknown
+        };
+
+        if !has_dx10 {
+            channels = match format {
+                DdsFormat::Compressed(4) => Some(1),
+                DdsFormat::Compressed(5) => Some(2),
+                DdsFormat::Compressed(_) => Some(3),
+                _ if pf_flags & DDPF_RGB != 0 => Some(3),
+                _ => None,
+            };
+        }
+        Self {
+            width,
+            height,
+            format,
+            srgb,
+            channels,
+            mipmaps,
+            faces,
+            cubemap,
+            header_size,
+            file_size,
+            loc,
+        }
+    }
+
+    /// Load the header without reporting any problems with it.
+    fn load_quietly(entry: &FileEntry) -> Result<Option<Self>> {
+        let file_size = metadata(entry.fullpath())?.len();
+        let (buffer, header_size) = read_header(entry)?;
+        if !buffer.starts_with(b"DDS ") {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(&buffer, header_size, file_size, entry.into())))
+    }
+
+    /// The number of mipmap levels in a full chain, down to 1x1.
+    fn full_mipmaps(&self) -> u32 {
+        self.width.max(self.height).max(1).ilog2() + 1
+    }
+
+    fn validate(&self, entry: &FileEntry) {
+        if self.width == 0 || self.height == 0 {
+            err(ErrorKey::ImageFormat).msg("texture has no width or height").loc(entry).push();
+            return;
+        }
+
+        match self.format {
+            DdsFormat::Unsupported(desc) => {
+                let msg = format!("texture is in {desc} format, which the game can't load");
+                err(ErrorKey::ImageFormat).msg(msg).loc(entry).push();
+                return;
+            }
+            DdsFormat::Unknown => {
+                let msg = "texture is in an unrecognized format";
+                let info = "the game may not be able to load it";
+                warn(ErrorKey::ImageFormat).weak().msg(msg).info(info).loc(entry).push();
+                return;
+            }
+            DdsFormat::Compressed(bc) => {
+                if self.width % 4 != 0 || self.height % 4 != 0 {
+                    let msg = format!(
+                        "BC{bc} compressed texture is {}x{}, which is not a multiple of 4",
+                        self.width, self.height
+                    );
+                    let info = "the game can't load compressed textures unless both sizes are multiples of 4";
+                    err(ErrorKey::ImageFormat).msg(msg).info(info).loc(entry).push();
+                }
+            }
+            DdsFormat::Uncompressed(_) => {
+                if entry.path().starts_with("gfx/interface/icons") {
+                    let msg = "icon texture is not compressed";
+                    let info = "BC3 or BC7 compression makes it about 4 times smaller with little quality loss";
+                    tips(ErrorKey::ImageFormat).msg(msg).info(info).loc(entry).push();
+                }
+            }
+        }
+
+        if self.cubemap {
+            if self.faces % 6 != 0 {
+                let msg = "cubemap does not have all 6 faces";
+                err(ErrorKey::ImageFormat).msg(msg).loc(entry).push();
+            }
+            if self.width != self.height {
+                let msg = format!(
+                    "cubemap faces are {}x{}, but should be square",
+                    self.width, self.height
+                );
+                err(ErrorKey::ImageFormat).msg(msg).loc(entry).push();
+            }
+        }
+
+        let full = self.full_mipmaps();
+        if self.mipmaps > full {
+            let msg = format!(
+                "texture claims {} mipmap levels, but {full} is the most possible",
+                self.mipmaps
+            );
+            err(ErrorKey::ImageFormat).msg(msg).loc(entry).push();
+        } else if self.mipmaps > 1 && self.mipmaps < full {
+            let msg = format!("incomplete mipmap chain: {} of {full} levels", self.mipmaps);
+            let info = "the texture may look wrong when shown at small sizes";
+            warn(ErrorKey::ImageFormat).weak().msg(msg).info(info).loc(entry).push();
+        } else if self.mipmaps == 1
+            && (entry.path().starts_with("gfx/models") || entry.path().starts_with("gfx/map"))
+        {
+            let msg = "texture has no mipmaps";
+            let info =
+                "textures shown in the 3d world look grainy and render slower without mipmaps";
+            tips(ErrorKey::ImageFormat).msg(msg).info(info).loc(entry).push();
+        }
+
+        if self.srgb == Some(true) {
+            let filename = entry.filename().to_string_lossy();
+            if filename.ends_with("_normal.dds") || filename.ends_with("_properties.dds") {
+                let msg =
+                    "texture is in an sRGB format, but normal and properties maps hold linear data";
+                let info = "use the non-sRGB (UNORM) variant of the format";
+                warn(ErrorKey::ImageFormat).msg(msg).info(info).loc(entry).push();
+            }
+        }
+
+        if let Some(expected) = self.expected_size() {
+            if self.file_size < expected {
+                let msg = "texture file is truncated";
+                let info = format!(
+                    "the header describes {expected} bytes, but the file has {}",
+                    self.file_size
+                );
+                err(ErrorKey::ImageFormat).msg(msg).info(info).loc(entry).push();
+            }
+        }
+    }
+
+    /// The file size needed to hold the data described by the header, if known.
+    fn expected_size(&self) -> Option<u64> {
+        let mut size = 0;
+        let (mut width, mut height) = (self.width, self.height);
+        for _ in 0..self.mipmaps {
+            size += self.format.data_size(width, height)?;
+            width = (width >> 1).max(1);
+            height = (height >> 1).max(1);
+        }
+        Some(self.header_size as u64 + size * u64::from(self.faces.max(1)))
+    }
+
+    fn compare_vanilla(&self, vanilla: &DdsInfo) {
+        if self.file_size > vanilla.file_size * SIZE_RATIO_LIMIT {
+            let msg = format!(
+                "texture is more than {SIZE_RATIO_LIMIT} times larger than the base game's version"
+            );
+            let info = format!(
+                "the base game's version is {}x{} and {} bytes; large textures use memory and slow down loading",
+                vanilla.width, vanilla.height, vanilla.file_size
+            );
+            tips(ErrorKey::ImageFormat).msg(msg).info(info).loc(self.loc).push();
+        }
+        if let (Some(srgb), Some(vanilla_srgb)) = (self.srgb, vanilla.srgb) {
+            if srgb != vanilla_srgb {
+                let (ours, theirs) = if srgb { ("sRGB", "linear") } else { ("linear", "sRGB") };
+                let msg = format!("texture is {ours} but the base game's version is {theirs}");
+                let info = "the colors will look too dark or too light in game";
+                warn(ErrorKey::ImageFormat)
+                    .msg(msg)
+                    .info(info)
+                    .loc(self.loc)
+                    .loc_msg(vanilla.loc, "base game's version")
+                    .push();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::report::{take_reports, TEST_REPORTS_LOCK};
+
+    fn header(width: u32, height: u32, mipmaps: u32, fourcc: [u8; 4]) -> [u8; 148] {
+        let mut buffer = [0; 148];
+        buffer[..4].copy_from_slice(b"DDS ");
+        buffer[DDS_FLAGS_OFFSET..][..4].copy_from_slice(&DDSD_MIPMAPCOUNT.to_le_bytes());
+        buffer[DDS_HEIGHT_OFFSET..][..4].copy_from_slice(&height.to_le_bytes());
+        buffer[DDS_WIDTH_OFFSET..][..4].copy_from_slice(&width.to_le_bytes());
+        buffer[DDS_MIPMAPCOUNT_OFFSET..][..4].copy_from_slice(&mipmaps.to_le_bytes());
+        buffer[DDS_PIXELFORMAT_FLAGS_OFFSET..][..4].copy_from_slice(&DDPF_FOURCC.to_le_bytes());
+        buffer[DDS_FOURCC_OFFSET..][..4].copy_from_slice(&fourcc);
+        buffer
+    }
+
+    #[test]
+    fn test_dds_sizes() {
+        let loc =
+            Loc::for_file(PathBuf::from("test.dds"), FileKind::Mod, PathBuf::from("test.dds"));
+
+        let info = DdsInfo::new(&header(256, 128, 9, *b"DXT5"), DDS_HEADER_SIZE, 0, loc);
+        assert_eq!(info.format, DdsFormat::Compressed(3));
+        assert_eq!(info.full_mipmaps(), 9);
+        // 256x128 is 64x32 blocks of 16 bytes, and each mipmap level is a quarter of the previous
+        // one, down to the 1x1 block minimum for the last 2 levels.
+        let data = 16 * (2048 + 512 + 128 + 32 + 8 + 2 + 1 + 1 + 1);
+        assert_eq!(info.expected_size(), Some(128 + data));
+
+        let mut buffer = header(64, 64, 1, *b"DX10");
+        buffer[DX10_FORMAT_OFFSET..][..4].copy_from_slice(&99u32.to_le_bytes());
+        buffer[DX10_MISCFLAG_OFFSET..][..4].copy_from_slice(&DX10_MISC_TEXTURECUBE.to_le_bytes());
+        let info = DdsInfo::new(&buffer, DDS_HEADER_SIZE + DX10_HEADER_SIZE, 0, loc);
+        assert_eq!(info.format, DdsFormat::Compressed(7));
+        assert_eq!(info.srgb, Some(true));
+        assert!(info.cubemap);
+        assert_eq!(info.expected_size(), Some(148 + 6 * 16 * 256));
+    }
+
+    #[test]
+    fn test_short_header() {
+        let path = std::env::temp_dir().join(format!("tiger-short-{}.dds", std::process::id()));
+        let entry = FileEntry::new(PathBuf::from("short.dds"), FileKind::Mod, path.clone());
+        let buffer = header(4, 4, 1, *b"DXT1");
+
+        // The unused last field of the header may be missing.
+        std::fs::write(&path, &buffer[..DDS_MIN_HEADER_SIZE]).unwrap();
+        let (read, header_size) = read_header(&entry).unwrap();
+        assert_eq!(header_size, DDS_MIN_HEADER_SIZE);
+        assert_eq!(read[..DDS_MIN_HEADER_SIZE], buffer[..DDS_MIN_HEADER_SIZE]);
+
+        // A 4x4 DXT1 texture is a single block of 8 bytes, which follows the short header.
+        let file_size = DDS_MIN_HEADER_SIZE as u64 + 8;
+        let info = DdsInfo::new(&read, header_size, file_size, (&entry).into());
+        assert_eq!(info.expected_size(), Some(file_size));
+        let _guard = TEST_REPORTS_LOCK.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        take_reports();
+        info.validate(&entry);
+        let reports = take_reports();
+        assert!(!reports.iter().any(|report| report.msg == "texture file is truncated"));
+
+        std::fs::write(&path, &buffer[..DDS_MIN_HEADER_SIZE - 1]).unwrap();
+        assert!(read_header(&entry).is_err());
+        std::fs::remove_file(&path).unwrap();
     }
 }
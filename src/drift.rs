@@ -0,0 +1,142 @@
+//! Finding the changes that a game update made to the base game files that the mod replaces,
+//! by comparing them to the same files from an older version of the game.
+
+use std::fs::read;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::block::{Block, BlockItem, Field, BV};
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileKind};
+use crate::helpers::TigerHashMap;
+use crate::pdxfile::PdxFile;
+use crate::report::{warn, ErrorKey};
+use crate::token::Token;
+
+/// The top-level items of a script file, by key. Keys can be repeated, so each has a list.
+type Items<'a> = TigerHashMap<&'a str, Vec<(&'a Token, &'a BV)>>;
+
+/// Compare the base game files that the mod replaces with their versions in `old_dir`, which is
+/// the game directory of an older version of the game, and report the changes that the game
+/// made to them since then which the mod's copies don't have.
+pub fn check_drift(data: &Everything, old_dir: &Path) -> Result<()> {
+    let Some(game_dir) = data.fileset.vanilla_dir() else {
+        bail!("the game directory is needed to compare with an older version");
+    };
+    if !old_dir.join("game").is_dir() {
+        bail!("{} does not look like a game directory", old_dir.display());
+    }
+
+    for (vanilla, mine) in data.fileset.overridden_files() {
+        let Ok(relative) = vanilla.fullpath().strip_prefix(game_dir) else {
+            continue;
+        };
+        let old_path = old_dir.join(relative);
+        // Files that weren't in the old version were copied after that, so they can't be stale.
+        let (Ok(old_bytes), Ok(new_bytes)) = (read(&old_path), read(vanilla.fullpath())) else {
+            continue;
+        };
+        if old_bytes == new_bytes {
+            continue;
+        }
+        let old = FileEntry::new(vanilla.path().to_path_buf(), FileKind::Vanilla, old_path);
+
+        let filename = vanilla.filename().to_string_lossy();
+        if filename.ends_with(".txt") || filename.ends_with(".gui") {
+            let parsed = (
+                PdxFile::read_optional_bom(&old, &data.parser),
+                PdxFile::read_optional_bom(vanilla, &data.parser),
+                PdxFile::read_optional_bom(mine, &data.parser),
+            );
+            if let (Some(old), Some(new), Some(my)) = parsed {
+                if let (Some(old_items), Some(new_items), Some(my_items)) =
+                    (items(&old), items(&new), items(&my))
+                {
+                    compare_items(mine, &old_items, &new_items, &my_items);
+                } else if !new.equivalent(&old) {
+                    report_file(mine, my.equivalent(&old));
+                }
+                continue;
+            }
+        }
+        report_file(mine, read(mine.fullpath()).is_ok_and(|my_bytes| my_bytes == old_bytes));
+    }
+    Ok(())
+}
+
+/// Report a changed file that can't be compared item by item.
+fn report_file(mine: &FileEntry, unchanged: bool) {
+    let msg = "the base game changed this file since the older version";
+    let info = if unchanged {
+        "your copy is still the same as the older version"
+    } else {
+        "your copy differs from both versions, so the changes have to be merged by hand"
+    };
+    warn(ErrorKey::VanillaDrift).msg(msg).info(info).loc(mine).push();
+}
+
+/// Collect the top-level items of a script file, or return `None` if it has loose values or
+/// blocks at the top level, in which case it can't be compared item by item.
+fn items(block: &Block) -> Option<Items<'_>> {
+    let mut items = Items::default();
+    for item in block.iter_items() {
+        let BlockItem::Field(Field(key, _, bv)) = item else {
+            return None;
+        };
+        items.entry(key.as_str()).or_default().push((key, bv));
+    }
+    Some(items)
+}
+
+fn same(a: &[(&Token, &BV)], b: &[(&Token, &BV)]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|((_, a), (_, b))| a.equivalent(b))
+}
+
+fn compare_items(mine: &FileEntry, old: &Items, new: &Items, my: &Items) {
+    // The current items in file order, followed by the removed ones.
+    let mut keys: Vec<_> = new.values().map(|v| v[0].0).collect();
+    keys.sort_unstable_by_key(|key| key.loc);
+    let mut removed: Vec<_> =
+        old.iter().filter(|(name, _)| !new.contains_key(*name)).map(|(_, v)| v[0].0).collect();
+    removed.sort_unstable_by_key(|key| key.loc);
+    keys.extend(removed);
+
+    for key in keys {
+        let name = key.as_str();
+        match (old.get(name), new.get(name), my.get(name)) {
+            (None, Some(new), None) => {
+                let msg = format!(
+                    "the base game added `{name}` to this file, but your copy does not have it"
+                );
+                warn(ErrorKey::VanillaDrift)
+                    .msg(msg)
+                    .loc(mine)
+                    .loc_msg(new[0].0, "added here")
+                    .push();
+            }
+            (Some(_), None, Some(my)) => {
+                let msg = format!(
+                    "the base game removed `{name}` from this file, but your copy still has it"
+                );
+                let info = "it may have been renamed or moved to another file";
+                warn(ErrorKey::VanillaDrift).msg(msg).info(info).loc(my[0].0).push();
+            }
+            (Some(old), Some(new), Some(my)) if !same(old, new) && !same(my, new) => {
+                let msg = format!("the base game changed `{name}`");
+                let info = if same(my, old) {
+                    "your copy still has the older version"
+                } else {
+                    "your copy changes it too, so the changes have to be merged by hand"
+                };
+                warn(ErrorKey::VanillaDrift)
+                    .msg(msg)
+                    .info(info)
+                    .loc(my[0].0)
+                    .loc_msg(new[0].0, "current version")
+                    .push();
+            }
+            _ => (),
+        }
+    }
+}
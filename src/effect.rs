@@ -6,9 +6,11 @@ use crate::data::effect_localization::EffectLocalization;
 use crate::desc::validate_desc;
 use crate::everything::Everything;
 use crate::game::Game;
+use crate::game_data::GameData;
 use crate::item::Item;
 use crate::lowercase::Lowercase;
-use crate::report::{err, fatal, tips, warn, ErrorKey};
+use crate::macros::{macro_args, validate_macro_args};
+use crate::report::{err, fatal, tips, warn, ErrorKey, Severity};
 use crate::scopes::{scope_iterator, Scopes};
 use crate::script_value::validate_script_value;
 use crate::token::Token;
@@ -16,6 +18,7 @@ use crate::tooltipped::Tooltipped;
 #[cfg(any(feature = "ck3", feature = "imperator"))]
 use crate::trigger::validate_target_ok_this;
 use crate::trigger::{validate_target, validate_trigger};
+use crate::trigger_logic::check_ifelse_reachable;
 #[cfg(not(feature = "imperator"))]
 use crate::validate::validate_compare_duration;
 #[cfg(any(feature = "ck3", feature = "imperator"))]
@@ -27,6 +30,7 @@ use crate::validate::{
     validate_iterator_fields, validate_scope_chain, validate_scripted_modifier_call, ListType,
 };
 use crate::validator::{Validator, ValueValidator};
+use crate::value_range::{eval_script_value, eval_weight_modifiers};
 
 /// The standard interface to effect validation. Validates an effect in the given [`ScopeContext`].
 ///
@@ -104,6 +108,7 @@ pub fn validate_effect_internal(
     }
 
     validate_ifelse_sequence(block, "if", "else_if", "else");
+    check_ifelse_reachable(block, "if", "else_if", data);
 
     vd.set_allow_questionmark_equals(true);
     vd.unknown_fields_cmp(|key, cmp, bv| {
@@ -122,9 +127,11 @@ pub fn validate_effect_field(
     tooltipped: Tooltipped,
 ) {
     if let Some(effect) = data.get_effect(key) {
+        let parms = effect.macro_parms();
+        effect.record_call(key, bv, &macro_args(&parms, bv), sc);
         match bv {
             BV::Value(token) => {
-                if !effect.macro_parms().is_empty() {
+                if !parms.is_empty() {
                     fatal(ErrorKey::Macro).msg("expected macro arguments").loc(token).push();
                 } else if !token.is("yes") {
                     warn(ErrorKey::Validation).msg("expected just effect = yes").loc(token).push();
@@ -132,31 +139,15 @@ pub fn validate_effect_field(
                 effect.validate_call(key, data, sc, tooltipped);
             }
             BV::Block(block) => {
-                let parms = effect.macro_parms();
                 if parms.is_empty() {
                     err(ErrorKey::Macro)
                         .msg("this scripted effect does not need macro arguments")
                         .info("you can just use it as effect = yes")
                         .loc(block)
                         .push();
-                } else {
-                    let mut vec = Vec::new();
-                    let mut vd = Validator::new(block, data);
-                    for parm in &parms {
-                        if let Some(token) = vd.field_value(parm) {
-                            vec.push(token.clone());
-                        } else {
-                            let msg = format!("this scripted effect needs parameter {parm}");
-                            err(ErrorKey::Macro).msg(msg).loc(block).push();
-                            return;
-                        }
-                    }
-                    vd.unknown_value_fields(|key, _value| {
-                        let msg = format!("this scripted effect does not need parameter {key}");
-                        let info = "supplying an unneeded parameter often causes a crash";
-                        fatal(ErrorKey::Macro).msg(msg).info(info).loc(key).push();
-                    });
-                    let args: Vec<_> = parms.into_iter().zip(vec).collect();
+                } else if let Some(args) =
+                    validate_macro_args("scripted effect", &parms, block, data, Severity::Fatal)
+                {
                     effect.validate_macro_expansion(key, &args, data, sc, tooltipped);
                 }
             }
@@ -183,7 +174,9 @@ pub fn validate_effect_field(
         Game::Imperator => crate::imperator::tables::effects::scope_effect,
     };
 
-    if let Some((inscopes, effect)) = scope_effect(key, data) {
+    if let Some((inscopes, effect)) =
+        scope_effect(key, data).or_else(|| GameData::get()?.effect(key))
+    {
         sc.expect(inscopes, &Reason::Token(key.clone()));
         match effect {
             Effect::Yes => {
@@ -454,6 +447,14 @@ pub fn validate_effect_control(
     if caller == "random" {
         vd.req_field("chance");
         vd.field_script_value("chance", sc);
+        if let Some(bv) = block.get_field("chance") {
+            let chance = eval_weight_modifiers(eval_script_value(bv, data), block, data);
+            if chance.max <= 0.0 {
+                let msg = format!("chance is never more than 0 ({chance})");
+                let info = "this `random` block will never run";
+                warn(ErrorKey::Logic).msg(msg).info(info).loc(bv).push();
+            }
+        }
     } else {
         vd.ban_field("chance", || "`random`");
     }
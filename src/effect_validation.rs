@@ -15,8 +15,10 @@ use crate::script_value::validate_script_value;
 use crate::token::Token;
 use crate::tooltipped::Tooltipped;
 use crate::trigger::{validate_target_ok_this, validate_trigger_key_bv};
+use crate::trigger_logic::check_switch_cases;
 use crate::validate::validate_optional_duration;
 use crate::validator::{Validator, ValueValidator};
+use crate::value_range::{eval_weight_modifiers, ValueRange};
 
 #[allow(dead_code)] // No longer used by CK3
 pub fn validate_add_to_list(
@@ -106,6 +108,14 @@ pub fn validate_random_list(
                 let msg = "fractions are discarded in `random_list` weights";
                 warn(ErrorKey::Range).strong().msg(msg).loc(key).push();
             }
+            if n >= 0.0 {
+                let weight = eval_weight_modifiers(ValueRange::exact(n), block, data);
+                if weight.max <= 0.0 {
+                    let msg = "this `random_list` entry's weight can never be more than 0";
+                    let info = "it will never be picked";
+                    warn(ErrorKey::Logic).msg(msg).info(info).loc(key).push();
+                }
+            }
             validate_effect_control(&caller, block, data, sc, tooltipped);
         }
     });
@@ -194,7 +204,7 @@ pub fn validate_set_variable(
 /// A specific validator for the `switch` effect, which has a unique syntax.
 pub fn validate_switch(
     key: &Token,
-    _block: &Block,
+    block: &Block,
     data: &Everything,
     sc: &mut ScopeContext,
     mut vd: Validator,
@@ -205,6 +215,7 @@ pub fn validate_switch(
     if let Some(target) = vd.field_value("trigger") {
         // clone to avoid calling vd again while target is still borrowed
         let target = target.clone();
+        check_switch_cases(block);
         let mut count = 0;
         vd.set_allow_questionmark_equals(true);
         vd.unknown_block_fields(|key, block| {
@@ -4,12 +4,12 @@
 //! that individual functions can access all the defined game items.
 
 use std::borrow::Cow;
-use std::fmt::Debug;
+use std::fmt::{Debug, Write};
 use std::path::{Path, PathBuf};
 #[cfg(feature = "ck3")]
 use std::sync::RwLock;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use rayon::{scope, Scope};
 use strum::IntoEnumIterator;
 use thiserror::Error;
@@ -56,8 +56,11 @@ use crate::data::{
 };
 use crate::db::{Db, DbKind};
 use crate::dds::DdsFiles;
+use crate::drift::check_drift;
+use crate::expand::expand_call_site;
 use crate::fileset::{FileEntry, FileKind, Fileset};
 use crate::game::Game;
+use crate::game_data::GameData;
 #[cfg(feature = "ck3")]
 use crate::helpers::TigerHashSet;
 #[cfg(feature = "imperator")]
@@ -67,15 +70,21 @@ use crate::imperator::tables::misc::*;
 use crate::item::{Item, ItemLoader};
 use crate::lowercase::Lowercase;
 use crate::macros::MACRO_MAP;
+use crate::mesh::MeshFiles;
+use crate::overrides::check_overrides;
 #[cfg(feature = "vic3")]
 use crate::parse::json::parse_json_file;
 use crate::parse::ParserMemory;
 use crate::pdxfile::PdxFile;
+use crate::perf::check_performance;
 #[cfg(feature = "ck3")]
 use crate::report::err;
 use crate::report::{report, set_output_style, ErrorKey, OutputStyle, Severity};
 use crate::rivers::Rivers;
+use crate::scopes::Scopes;
+use crate::sound::SoundEvents;
 use crate::token::{Loc, Token};
+use crate::value_range::eval_script_value;
 #[cfg(feature = "vic3")]
 use crate::vic3::data::{
     buy_packages::BuyPackage, history::History, provinces::Vic3Provinces,
@@ -125,6 +134,12 @@ pub struct Everything {
     /// Tracks specifically the .dds files, and their formats and sizes.
     pub(crate) dds: DdsFiles,
 
+    /// The binary .mesh and .anim files that have been looked at, loaded on demand.
+    pub(crate) meshes: MeshFiles,
+
+    /// The sound events from the `GUIDs.txt` files and FMOD strings banks.
+    pub(crate) sounds: SoundEvents,
+
     /// A general database of item types. Most items go here. The ones that need special handling
     /// go in the separate databases listed below.
     pub(crate) database: Db,
@@ -249,6 +264,8 @@ impl Everything {
             parser: ParserMemory::default(),
             fileset,
             dds: DdsFiles::default(),
+            meshes: MeshFiles::default(),
+            sounds: SoundEvents::default(),
             config,
             #[cfg(feature = "ck3")]
             warned_defines: RwLock::new(TigerHashSet::default()),
@@ -365,7 +382,7 @@ impl Everything {
         let path = PathBuf::from("reader_export");
         for entry in self.fileset.get_files_under(&path) {
             if entry.filename().to_string_lossy().ends_with(".txt") {
-                PdxFile::reader_export(entry, &mut self.parser.pdxfile);
+                PdxFile::reader_export(entry, &mut self.parser);
             }
         }
     }
@@ -403,20 +420,41 @@ impl Everything {
 
     fn load_all_generic(&mut self) {
         scope(|s| {
-            s.spawn(|_| self.fileset.handle(&mut self.dds, &self.parser));
+            s.spawn(|_| {
+                self.fileset.handle(&mut self.dds, &self.parser);
+                for entry in self.fileset.get_replaced_files_under(Path::new("gfx")) {
+                    self.dds.load_replaced_file(entry);
+                }
+            });
             s.spawn(|_| self.fileset.handle(&mut self.events, &self.parser));
             s.spawn(|_| self.fileset.handle(&mut self.localization, &self.parser));
             s.spawn(|_| self.fileset.handle(&mut self.scripted_lists, &self.parser));
-            s.spawn(|_| self.fileset.handle(&mut self.defines, &self.parser));
+            s.spawn(|_| {
+                self.fileset.handle(&mut self.defines, &self.parser);
+                for entry in self.fileset.get_replaced_files_under(Path::new("common/defines")) {
+                    self.defines.load_replaced_file(entry, &self.parser);
+                }
+            });
             s.spawn(|_| self.fileset.handle(&mut self.scripted_modifiers, &self.parser));
             s.spawn(|_| self.fileset.handle(&mut self.script_values, &self.parser));
             s.spawn(|_| self.fileset.handle(&mut self.triggers, &self.parser));
             s.spawn(|_| self.fileset.handle(&mut self.effects, &self.parser));
             s.spawn(|_| self.fileset.handle(&mut self.assets, &self.parser));
-            s.spawn(|_| self.fileset.handle(&mut self.gui, &self.parser));
+            s.spawn(|_| {
+                self.fileset.handle(&mut self.gui, &self.parser);
+                for entry in self.fileset.get_replaced_files_under(Path::new("gui")) {
+                    self.gui.load_replaced_file(entry, &self.parser);
+                }
+            });
             s.spawn(|_| self.fileset.handle(&mut self.on_actions, &self.parser));
             s.spawn(|_| self.fileset.handle(&mut self.coas, &self.parser));
             s.spawn(|_| self.fileset.handle(&mut self.music, &self.parser));
+            s.spawn(|_| {
+                self.fileset.handle(&mut self.sounds, &self.parser);
+                for entry in self.fileset.get_replaced_files_under(Path::new("sound")) {
+                    self.sounds.load_replaced_file(entry, &self.parser);
+                }
+            });
         });
 
         self.load_all_normal_pdx_files();
@@ -535,6 +573,11 @@ impl Everything {
             }
         });
         self.database.validate(self);
+        self.triggers.check_calls();
+        self.effects.check_calls();
+        self.parser.pdxfile.check_unused(&self.parser.usage);
+        check_performance(self);
+        check_overrides(self);
 
         self.localization.validate_pass2(self);
     }
@@ -557,6 +600,92 @@ impl Everything {
         self.fileset.check_unused_dds(self);
     }
 
+    /// Add the mod's localization entries that are missing in the `langs` languages, by copying
+    /// them from the `from` language into the corresponding per-language files.
+    /// See [`Localization::sync`].
+    pub fn sync_localization(
+        &self,
+        from: &str,
+        langs: &[&str],
+        marker: &str,
+    ) -> Result<Vec<(PathBuf, usize)>> {
+        self.localization.sync(self.fileset.mod_root(), from, langs, marker)
+    }
+
+    /// Render a localization key as plain text, with its macros expanded and its `[code]`
+    /// annotated with datatypes. See [`Localization::render`].
+    ///
+    /// `scopes` are pairs of scope names and their types, such as `("root", "character")`.
+    pub fn render_localization(
+        &self,
+        key: &str,
+        lang: &str,
+        scopes: &[(String, String)],
+    ) -> Result<String> {
+        let mut named = Vec::new();
+        for (name, stype) in scopes {
+            let Some(scopes) = Scopes::from_snake_case_multi(stype) else {
+                bail!("unknown scope type {stype}");
+            };
+            named.push((name.as_str(), scopes));
+        }
+        self.localization.render(key, lang, &named, self)
+    }
+
+    /// Return the fully resolved widget tree of the named window or widget type, as `.gui` script.
+    /// See [`Gui::dump`].
+    pub fn dump_gui(&self, name: &str) -> Result<String> {
+        self.gui.dump(name)
+    }
+
+    /// Return the range of values that the named script value can have, such as `[0, 100]`.
+    /// Parts of the calculation that depend on the game state make the range unbounded.
+    pub fn eval_script_value(&self, name: &str) -> Result<String> {
+        let Some(bv) = self.script_values.get_bv(name) else {
+            bail!("script value {name} not found");
+        };
+        Ok(eval_script_value(bv, self).to_string())
+    }
+
+    /// Describe the definitions of a reader variable (`@name`), global ones first, with their
+    /// values and where they are.
+    pub fn query_constant(&self, name: &str) -> Result<String> {
+        let name = name.strip_prefix('@').unwrap_or(name);
+        let found = self.parser.pdxfile.query_variable(name, &self.parser.usage);
+        if found.is_empty() {
+            bail!("reader variable @{name} is not defined");
+        }
+        let mut out = String::new();
+        for (key, value, is_global) in found {
+            let scope = if is_global { "global" } else { "only in this file" };
+            let path = key.loc.pathname().display();
+            _ = writeln!(out, "{path}:{}: @{name} = {value}  # {scope}", key.loc.line);
+        }
+        Ok(out)
+    }
+
+    /// Compare the base game files that the mod replaces with the same files in `old_dir`, the game
+    /// directory of an older version, and report the changes the mod's copies are missing.
+    pub fn check_drift(&self, old_dir: &Path) -> Result<()> {
+        check_drift(self, old_dir)
+    }
+
+    /// List the defines that the mod changes or adds, with their vanilla and new values.
+    pub fn defines_diff(&self) -> String {
+        self.defines.diff()
+    }
+
+    /// Return the script run by the scripted triggers and effects called on a line of a file,
+    /// with their macro arguments filled in and the calls inside them expanded as well.
+    pub fn expand_call_site(&self, path: &Path, line: u32) -> Result<String> {
+        expand_call_site(self, path, line)
+    }
+
+    /// Return the languages that the mod has localization files for.
+    pub fn mod_languages(&self) -> &[&'static str] {
+        self.localization.mod_langs()
+    }
+
     pub(crate) fn item_has_property(&self, itype: Item, key: &str, property: &str) -> bool {
         self.database.has_property(itype, key, property, self)
     }
@@ -998,7 +1127,16 @@ impl Everything {
         // TODO: verify that file:/ values work
         if let Some(filename) = name.strip_prefix("file:/") {
             self.fileset.exists(filename)
+        } else if self.sounds.exists(name) {
+            true
+        } else if self.sounds.has_vanilla_events() {
+            false
+        } else if let Some(known) =
+            GameData::get().and_then(|game_data| game_data.list_contains("sounds", name))
+        {
+            known
         } else {
+            // Fall back to the built-in list if the game's sound events could not be loaded
             let sounds_set = match Game::game() {
                 #[cfg(feature = "ck3")]
                 Game::Ck3 => &crate::ck3::tables::sounds::SOUNDS_SET,
@@ -0,0 +1,216 @@
+//! Writing out the script that a call to a scripted trigger or effect runs, with all the macro
+//! arguments filled in and the scripted triggers and effects inside it expanded too.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::block::{Block, BlockItem, Field, BV};
+use crate::everything::Everything;
+use crate::macros::macro_args;
+use crate::pdxfile::PdxFile;
+use crate::token::{Loc, Token};
+
+/// Expansions nested deeper than this are assumed to be recursive, which the game doesn't allow.
+const MAX_DEPTH: usize = 16;
+
+/// Fields whose contents are triggers even when they appear in an effect.
+const TRIGGER_FIELDS: &[&str] = &[
+    "limit",
+    "trigger",
+    "is_shown",
+    "is_valid",
+    "is_valid_showing_failures_only",
+    "potential",
+    "allow",
+    "can_start",
+];
+
+/// Whether the script being written is a trigger or an effect. This decides whether a call refers
+/// to a scripted trigger or a scripted effect when both exist with the same name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Context {
+    Unknown,
+    Trigger,
+    Effect,
+}
+
+/// Find the call to a scripted trigger or effect on `line` of the file at `path`, and return its
+/// expansion as script text. `path` can be relative to the mod or the game directory, or a full
+/// path inside the mod.
+pub fn expand_call_site(data: &Everything, path: &Path, line: u32) -> Result<String> {
+    let path = path.strip_prefix(data.fileset.mod_root()).unwrap_or(path);
+    let Some(entry) = data.fileset.get_entry(&path.to_string_lossy()) else {
+        bail!("file {} is not loaded", path.display());
+    };
+    let Some(block) = PdxFile::read_optional_bom(entry, &data.parser) else {
+        bail!("could not parse {}", path.display());
+    };
+    let mut calls = Vec::new();
+    find_calls(&block, line, data, &mut calls);
+    if calls.is_empty() {
+        bail!("no call to a scripted trigger or effect on line {line} of {}", path.display());
+    }
+
+    let mut expander = Expander { data, out: String::new(), stack: Vec::new() };
+    for (key, cmp, bv) in calls {
+        _ = writeln!(expander.out, "# {}: {key} {cmp} {}", describe_loc(key.loc), summary(bv));
+        expander.call(key, bv, Context::Unknown, 0);
+    }
+    Ok(expander.out)
+}
+
+/// Collect the fields on `line` that call a scripted trigger or effect.
+fn find_calls<'a>(
+    block: &'a Block,
+    line: u32,
+    data: &Everything,
+    calls: &mut Vec<(&'a Token, String, &'a BV)>,
+) {
+    for item in block.iter_items() {
+        match item {
+            BlockItem::Field(Field(key, cmp, bv)) => {
+                if key.loc.line == line
+                    && (data.get_trigger(key).is_some() || data.get_effect(key).is_some())
+                {
+                    calls.push((key, cmp.to_string(), bv));
+                }
+                if let Some(block) = bv.get_block() {
+                    find_calls(block, line, data, calls);
+                }
+            }
+            BlockItem::Block(block) => find_calls(block, line, data, calls),
+            BlockItem::Value(_) => (),
+        }
+    }
+}
+
+struct Expander<'a> {
+    data: &'a Everything,
+    out: String,
+    /// The names of the scripted triggers and effects currently being expanded.
+    stack: Vec<String>,
+}
+
+impl Expander<'_> {
+    /// Write the expansion of a call to a scripted trigger or effect, at the given indentation.
+    /// Returns false if `key` is not a scripted trigger or effect.
+    fn call(&mut self, key: &Token, bv: &BV, context: Context, indent: usize) -> bool {
+        let trigger = self.data.get_trigger(key);
+        let effect = self.data.get_effect(key);
+        let use_trigger = match (trigger.is_some(), effect.is_some()) {
+            (true, true) => context != Context::Effect,
+            (true, false) => true,
+            (false, true) => false,
+            (false, false) => return false,
+        };
+
+        let prefix = " ".repeat(indent * 4);
+        if self.stack.iter().any(|name| key.is(name)) || self.stack.len() >= MAX_DEPTH {
+            _ = writeln!(self.out, "{prefix}# {key} is recursive and is not expanded again");
+            return true;
+        }
+
+        let (definition, args, body) = if use_trigger {
+            let trigger = trigger.unwrap();
+            let args = macro_args(&trigger.macro_parms(), bv);
+            (&trigger.key, args.clone(), trigger.body(key, &args, self.data))
+        } else {
+            let effect = effect.unwrap();
+            let args = macro_args(&effect.macro_parms(), bv);
+            (&effect.key, args.clone(), effect.body(key, &args, self.data))
+        };
+        let what = if use_trigger { "scripted trigger" } else { "scripted effect" };
+        _ = writeln!(self.out, "{prefix}# {what} {key} from {}", describe_loc(definition.loc));
+        for (parm, value) in &args {
+            _ = writeln!(self.out, "{prefix}#   ${parm}$ = {}", quoted(value.as_str()));
+        }
+        let Some(body) = body else {
+            _ = writeln!(self.out, "{prefix}# could not expand the macro arguments");
+            return true;
+        };
+
+        let context = if use_trigger { Context::Trigger } else { Context::Effect };
+        self.stack.push(key.to_string());
+        if use_trigger && bv.get_value().is_some_and(|value| value.is("no")) {
+            _ = writeln!(self.out, "{prefix}NOT = {{");
+            self.block_contents(&body, context, indent + 1);
+            _ = writeln!(self.out, "{prefix}}}");
+        } else {
+            self.block_contents(&body, context, indent);
+        }
+        self.stack.pop();
+        true
+    }
+
+    fn block_contents(&mut self, block: &Block, context: Context, indent: usize) {
+        let prefix = " ".repeat(indent * 4);
+        for item in block.iter_items() {
+            match item {
+                BlockItem::Value(token) => {
+                    _ = writeln!(self.out, "{prefix}{}", quoted(token.as_str()));
+                }
+                BlockItem::Block(block) => {
+                    _ = write!(self.out, "{prefix}");
+                    self.block(block, context, indent);
+                }
+                BlockItem::Field(Field(key, cmp, bv)) => {
+                    if self.call(key, bv, context, indent) {
+                        continue;
+                    }
+                    _ = write!(self.out, "{prefix}{} {cmp} ", quoted(key.as_str()));
+                    match bv {
+                        BV::Value(token) => _ = writeln!(self.out, "{}", quoted(token.as_str())),
+                        BV::Block(block) => {
+                            let context = if TRIGGER_FIELDS.iter().any(|field| key.is(field)) {
+                                Context::Trigger
+                            } else {
+                                context
+                            };
+                            self.block(block, context, indent);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Write a block, starting at the current position in the output.
+    fn block(&mut self, block: &Block, context: Context, indent: usize) {
+        if let Some(tag) = &block.tag {
+            _ = write!(self.out, "{tag} ");
+        }
+        _ = writeln!(self.out, "{{");
+        self.block_contents(block, context, indent + 1);
+        _ = writeln!(self.out, "{}}}", " ".repeat(indent * 4));
+    }
+}
+
+fn describe_loc(loc: Loc) -> String {
+    format!("{}:{}", loc.pathname().display(), loc.line)
+}
+
+/// A short form of the right-hand side of a call, for the header of the expansion.
+fn summary(bv: &BV) -> String {
+    match bv {
+        BV::Value(token) => quoted(token.as_str()),
+        BV::Block(block) => {
+            let mut s = "{".to_owned();
+            for (key, token) in block.iter_assignments() {
+                _ = write!(s, " {key} = {}", quoted(token.as_str()));
+            }
+            s.push_str(" }");
+            s
+        }
+    }
+}
+
+/// Write a value the way it would appear in a script file, with quotes if it needs them.
+fn quoted(s: &str) -> String {
+    if s.is_empty() || s.contains(|c: char| c.is_whitespace() || c == '"' || c == '#') {
+        format!("\"{}\"", s.replace('"', "\\\""))
+    } else {
+        s.to_owned()
+    }
+}
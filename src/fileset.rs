@@ -3,6 +3,7 @@
 use std::cmp::Ordering;
 use std::ffi::OsStr;
 use std::fmt::{Display, Formatter};
+use std::mem::replace;
 use std::path::{Path, PathBuf};
 use std::string::ToString;
 use std::sync::RwLock;
@@ -230,6 +231,9 @@ pub struct Fileset {
     /// The CK3 and mod files in the order the game would load them.
     ordered_files: Vec<FileEntry>,
 
+    /// The files that are not loaded because a later file has the same path, in pathname order.
+    replaced_files: Vec<FileEntry>,
+
     /// Filename Tokens for the files in `ordered_files`.
     /// Used for [`Fileset::iter_keys()`].
     filename_tokens: Vec<Token>,
@@ -260,6 +264,7 @@ impl Fileset {
             config: None,
             files: Vec::new(),
             ordered_files: Vec::new(),
+            replaced_files: Vec::new(),
             filename_tokens: Vec::new(),
             filenames: TigerHashSet::default(),
             directories: RwLock::new(TigerHashSet::default()),
@@ -430,7 +435,7 @@ impl Fileset {
         for entry in self.files.drain(..) {
             if let Some(prev) = self.ordered_files.last_mut() {
                 if entry.path == prev.path {
-                    *prev = entry;
+                    self.replaced_files.push(replace(prev, entry));
                 } else {
                     self.ordered_files.push(entry);
                 }
@@ -445,6 +450,47 @@ impl Fileset {
             entry.store_in_pathtable();
             self.filenames.insert(entry.path.clone());
         }
+        for entry in &mut self.replaced_files {
+            entry.store_in_pathtable();
+        }
+    }
+
+    /// The directory of the mod being validated.
+    pub fn mod_root(&self) -> &Path {
+        self.the_mod.root()
+    }
+
+    /// The game directory, as given on the command line.
+    pub fn vanilla_dir(&self) -> Option<&Path> {
+        self.vanilla_root.as_deref().and_then(Path::parent)
+    }
+
+    /// Return the base game files that the mod under scrutiny replaces with its own copy,
+    /// each paired with the mod's file.
+    /// If a base game file was also replaced by a DLC file, only the DLC version is returned.
+    pub fn overridden_files(&self) -> Vec<(&FileEntry, &FileEntry)> {
+        let mut result: Vec<(&FileEntry, &FileEntry)> = Vec::new();
+        for entry in &self.replaced_files {
+            if !entry.kind.counts_as_vanilla() {
+                continue;
+            }
+            let Ok(idx) = self.ordered_files.binary_search_by_key(&&entry.path, |fe| &fe.path)
+            else {
+                continue;
+            };
+            let mine = &self.ordered_files[idx];
+            if mine.kind != FileKind::Mod {
+                continue;
+            }
+            if let Some(prev) = result.last_mut() {
+                if prev.0.path == entry.path {
+                    prev.0 = entry;
+                    continue;
+                }
+            }
+            result.push((entry, mine));
+        }
+        result
     }
 
     pub fn get_files_under<'a>(&'a self, subpath: &'a Path) -> &'a [FileEntry] {
@@ -454,6 +500,14 @@ impl Fileset {
         &self.ordered_files[start..end]
     }
 
+    /// Like [`Fileset::get_files_under`], but for the files that were replaced by later files.
+    pub fn get_replaced_files_under<'a>(&'a self, subpath: &'a Path) -> &'a [FileEntry] {
+        let start = self.replaced_files.partition_point(|entry| entry.path < subpath);
+        let end = start
+            + self.replaced_files[start..].partition_point(|entry| entry.path.starts_with(subpath));
+        &self.replaced_files[start..end]
+    }
+
     pub fn filter_map_under<F, T>(&self, subpath: &Path, f: F) -> Vec<T>
     where
         F: Fn(&FileEntry) -> Option<T> + Sync + Send,
@@ -487,6 +541,14 @@ impl Fileset {
         self.filenames.contains(&filepath)
     }
 
+    pub fn get_entry(&self, key: &str) -> Option<&FileEntry> {
+        let key = key.strip_prefix('/').unwrap_or(key);
+        let filepath = Path::new(key);
+        let idx =
+            self.ordered_files.binary_search_by_key(&filepath, |fe| fe.path.as_path()).ok()?;
+        Some(&self.ordered_files[idx])
+    }
+
     pub fn iter_keys(&self) -> impl Iterator<Item = &Token> {
         self.filename_tokens.iter()
     }
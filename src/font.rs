@@ -0,0 +1,190 @@
+//! Reader for the character map of `.ttf` and `.otf` font files.
+//!
+//! Only the `cmap` table is read, to find out which characters the font has glyphs for.
+
+use std::fs::read;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+fn be16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data.get(offset..offset + 2).context("unexpected end of font data")?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn be32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data.get(offset..offset + 4).context("unexpected end of font data")?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn be32_usize(data: &[u8], offset: usize) -> Result<usize> {
+    Ok(usize::try_from(be32(data, offset)?)?)
+}
+
+/// The characters that a font has glyphs for.
+#[derive(Clone, Debug, Default)]
+pub struct CharMap {
+    /// Sorted, non-overlapping ranges of covered characters.
+    ranges: Vec<RangeInclusive<u32>>,
+}
+
+impl CharMap {
+    pub fn read(path: &Path) -> Result<Self> {
+        Self::parse(&read(path)?)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut font = 0;
+        // A font collection has a header pointing to the individual fonts. Use the first one.
+        if data.starts_with(b"ttcf") {
+            font = be32_usize(data, 12)?;
+        }
+        let version = be32(data, font)?;
+        if version != 0x0001_0000 && version != u32::from_be_bytes(*b"OTTO") {
+            bail!("not a TrueType or OpenType font");
+        }
+
+        let num_tables = usize::from(be16(data, font + 4)?);
+        let mut cmap = None;
+        for i in 0..num_tables {
+            let record = font + 12 + i * 16;
+            if data.get(record..record + 4) == Some(b"cmap") {
+                cmap = Some(be32_usize(data, record + 8)?);
+            }
+        }
+        let Some(cmap) = cmap else {
+            bail!("font has no cmap table");
+        };
+
+        // Pick the most complete Unicode subtable
+        let mut best = None;
+        for i in 0..usize::from(be16(data, cmap + 2)?) {
+            let record = cmap + 4 + i * 8;
+            let platform = be16(data, record)?;
+            let encoding = be16(data, record + 2)?;
+            let subtable = cmap + be32_usize(data, record + 4)?;
+            let format = be16(data, subtable)?;
+            let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+            if unicode && (format == 12 || (format == 4 && best.is_none())) {
+                best = Some((format, subtable));
+            }
+        }
+        let ranges = match best {
+            Some((12, subtable)) => Self::parse_format12(data, subtable)?,
+            Some((_, subtable)) => Self::parse_format4(data, subtable)?,
+            None => bail!("font has no Unicode character map"),
+        };
+        Ok(Self::from_ranges(ranges))
+    }
+
+    fn from_ranges(mut ranges: Vec<RangeInclusive<u32>>) -> Self {
+        ranges.sort_unstable_by_key(|r| *r.start());
+        let mut merged: Vec<RangeInclusive<u32>> = Vec::new();
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                    *last = *last.start()..=*last.end().max(range.end());
+                }
+                _ => merged.push(range),
+            }
+        }
+        CharMap { ranges: merged }
+    }
+
+    /// Format 4 covers the Basic Multilingual Plane with segments that map to glyphs either by
+    /// adding a delta or through an array of glyph ids. In the latter case, a glyph id of 0 means
+    /// the character is missing.
+    fn parse_format4(data: &[u8], subtable: usize) -> Result<Vec<RangeInclusive<u32>>> {
+        let seg_count = usize::from(be16(data, subtable + 6)? >> 1);
+        let end_codes = subtable + 14;
+        let start_codes = end_codes + seg_count * 2 + 2;
+        // Skip the id deltas, which don't affect coverage
+        let range_offsets = start_codes + seg_count * 4;
+
+        let mut ranges = Vec::new();
+        for seg in 0..seg_count {
+            let end = be16(data, end_codes + seg * 2)?;
+            let start = be16(data, start_codes + seg * 2)?;
+            let range_offset_pos = range_offsets + seg * 2;
+            let range_offset = usize::from(be16(data, range_offset_pos)?);
+            if start > end || start == 0xFFFF {
+                continue;
+            }
+            if range_offset == 0 {
+                ranges.push(u32::from(start)..=u32::from(end));
+                continue;
+            }
+            for c in start..=end {
+                let pos = range_offset_pos + range_offset + usize::from(c - start) * 2;
+                if be16(data, pos)? != 0 {
+                    ranges.push(u32::from(c)..=u32::from(c));
+                }
+            }
+        }
+        Ok(ranges)
+    }
+
+    /// Format 12 covers all of Unicode with groups of consecutive characters.
+    fn parse_format12(data: &[u8], subtable: usize) -> Result<Vec<RangeInclusive<u32>>> {
+        let num_groups = be32_usize(data, subtable + 12)?;
+        let mut ranges = Vec::new();
+        for i in 0..num_groups {
+            let group = subtable + 16 + i * 12;
+            let start = be32(data, group)?;
+            let end = be32(data, group + 4)?;
+            if start <= end {
+                ranges.push(start..=end);
+            }
+        }
+        Ok(ranges)
+    }
+
+    pub fn covers(&self, c: char) -> bool {
+        let c = u32::from(c);
+        let idx = self.ranges.partition_point(|r| *r.end() < c);
+        self.ranges.get(idx).is_some_and(|r| r.contains(&c))
+    }
+
+    /// Add the characters covered by another font, for fonts that are used as fallbacks.
+    pub fn extend(&mut self, other: &CharMap) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.extend(other.ranges.iter().cloned());
+        *self = Self::from_ranges(ranges);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn be16s(values: &[u16]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn test_format4() {
+        let mut data = Vec::new();
+        data.extend(0x0001_0000u32.to_be_bytes());
+        data.extend(be16s(&[1, 0, 0, 0]));
+        data.extend(b"cmap");
+        data.extend(be16s(&[0, 0, 0, 28, 0, 0]));
+        // cmap header with one Windows Unicode subtable
+        data.extend(be16s(&[0, 1, 3, 1, 0, 12]));
+        // Segments A-Z (by delta), è-é (by glyph array, è missing), and the final 0xFFFF
+        data.extend(be16s(&[4, 0, 0, 6, 0, 0, 0]));
+        data.extend(be16s(&[0x5a, 0xe9, 0xffff, 0]));
+        data.extend(be16s(&[0x41, 0xe8, 0xffff]));
+        data.extend(be16s(&[3, 0, 1]));
+        data.extend(be16s(&[0, 4, 0]));
+        data.extend(be16s(&[0, 5]));
+
+        let charmap = CharMap::parse(&data).unwrap();
+        assert!(charmap.covers('A'));
+        assert!(charmap.covers('Z'));
+        assert!(!charmap.covers('a'));
+        assert!(!charmap.covers('è'));
+        assert!(charmap.covers('é'));
+        assert!(!charmap.covers('\u{ffff}'));
+    }
+}
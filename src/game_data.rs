@@ -0,0 +1,272 @@
+//! Tables of game items extracted from an installed game by the `extract-tables` utility.
+//!
+//! The built-in tables are only updated with each tiger release. Loading these tables with
+//! `--game-data` lets tiger accept triggers, effects, and other builtin items that were added by
+//! a game patch. Items that are only known from the game data can't be validated in detail, but at
+//! least they are not reported as unknown.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use once_cell::sync::OnceCell;
+
+use crate::block::Block;
+use crate::effect::Effect;
+use crate::fileset::{FileEntry, FileKind};
+use crate::game::Game;
+use crate::helpers::{TigerHashMap, TigerHashSet};
+use crate::lowercase::Lowercase;
+use crate::parse::ParserMemory;
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::Trigger;
+
+/// The name of the file written by `extract-tables`.
+pub const GAME_DATA_FILE: &str = "game_data.txt";
+
+/// The version of the file format. It is increased whenever the format changes in a way that
+/// older versions of tiger can't read.
+pub const GAME_DATA_FORMAT: i64 = 1;
+
+static GAME_DATA: OnceCell<GameData> = OnceCell::new();
+
+#[derive(Debug, Default)]
+pub struct GameData {
+    /// The game version that the tables were extracted from.
+    game_version: String,
+    triggers: TigerHashMap<String, Scopes>,
+    effects: TigerHashMap<String, Scopes>,
+    /// Event targets, with their input and output scopes.
+    event_targets: TigerHashMap<String, (Scopes, Scopes)>,
+    modifiers: TigerHashSet<String>,
+    /// Builtin on-actions, with their root scope.
+    on_actions: TigerHashMap<String, Scopes>,
+    /// Lists of names from the game's object browser, such as `sounds`.
+    lists: TigerHashMap<String, TigerHashSet<Lowercase<'static>>>,
+    /// The languages that the game has localization for.
+    languages: Vec<String>,
+}
+
+impl GameData {
+    /// Load the game data file from the given directory. Should be called before loading the mod.
+    ///
+    /// Returns the game version that the tables were extracted from.
+    pub fn load(dir: &Path) -> Result<&'static str> {
+        let path = dir.join(GAME_DATA_FILE);
+        if !path.is_file() {
+            bail!("Could not find {}", path.display());
+        }
+        let entry = FileEntry::new(PathBuf::from(GAME_DATA_FILE), FileKind::Internal, path);
+        let Some(block) = PdxFile::read_optional_bom(&entry, &ParserMemory::default()) else {
+            bail!("Could not read {}", entry.fullpath().display());
+        };
+        let game_data = Self::from_block(&block)?;
+        let game_data = GAME_DATA
+            .try_insert(game_data)
+            .map_err(|_| anyhow!("tried to load game data twice"))?;
+        Ok(&game_data.game_version)
+    }
+
+    fn from_block(block: &Block) -> Result<Self> {
+        let format = block.get_field_integer("format_version");
+        if format != Some(GAME_DATA_FORMAT) {
+            bail!("Game data has format version {format:?} but this tiger can only read version {GAME_DATA_FORMAT}. Please run extract-tables again.");
+        }
+        let game = match Game::game() {
+            #[cfg(feature = "ck3")]
+            Game::Ck3 => "ck3",
+            #[cfg(feature = "vic3")]
+            Game::Vic3 => "vic3",
+            #[cfg(feature = "imperator")]
+            Game::Imperator => "imperator",
+        };
+        if !block.get_field_value("game").is_some_and(|token| token.is(game)) {
+            bail!("Game data is not for {game}");
+        }
+
+        let mut game_data = GameData {
+            game_version: block
+                .get_field_value("game_version")
+                .map_or_else(|| "unknown".to_owned(), ToString::to_string),
+            ..Default::default()
+        };
+        for (key, token) in
+            block.get_field_block("triggers").iter().flat_map(|b| b.iter_assignments())
+        {
+            game_data.triggers.insert(key.as_str().to_ascii_lowercase(), scopes(token));
+        }
+        for (key, token) in
+            block.get_field_block("effects").iter().flat_map(|b| b.iter_assignments())
+        {
+            game_data.effects.insert(key.as_str().to_ascii_lowercase(), scopes(token));
+        }
+        for (key, b) in
+            block.get_field_block("event_targets").iter().flat_map(|b| b.iter_definitions())
+        {
+            let input = b.get_field_value("input").map_or(Scopes::all(), scopes);
+            let output = b.get_field_value("output").map_or(Scopes::all_but_none(), scopes);
+            game_data.event_targets.insert(key.as_str().to_ascii_lowercase(), (input, output));
+        }
+        for token in block.get_field_list("modifiers").unwrap_or_default() {
+            game_data.modifiers.insert(token.as_str().to_ascii_lowercase());
+        }
+        for (key, token) in
+            block.get_field_block("on_actions").iter().flat_map(|b| b.iter_assignments())
+        {
+            game_data.on_actions.insert(key.to_string(), scopes(token));
+        }
+        for (key, b) in block.get_field_block("lists").iter().flat_map(|b| b.iter_definitions()) {
+            let list = b
+                .iter_values()
+                .map(|token| Lowercase::from_string_unchecked(token.as_str().to_lowercase()));
+            game_data.lists.insert(key.to_string(), list.collect());
+        }
+        for token in block.get_field_list("languages").unwrap_or_default() {
+            game_data.languages.push(token.to_string());
+        }
+        Ok(game_data)
+    }
+
+    /// Return the loaded game data, if any.
+    pub fn get() -> Option<&'static GameData> {
+        GAME_DATA.get()
+    }
+
+    pub fn game_version(&self) -> &str {
+        &self.game_version
+    }
+
+    /// Look up a trigger that is only known from the game data.
+    pub(crate) fn trigger(&self, name: &Token) -> Option<(Scopes, Trigger)> {
+        let scopes = self.triggers.get(&name.as_str().to_ascii_lowercase())?;
+        Some((*scopes, Trigger::Unchecked))
+    }
+
+    /// Look up an effect that is only known from the game data.
+    pub(crate) fn effect(&self, name: &Token) -> Option<(Scopes, Effect)> {
+        let scopes = self.effects.get(&name.as_str().to_ascii_lowercase())?;
+        Some((*scopes, Effect::Unchecked))
+    }
+
+    /// Look up an event target that is only known from the game data.
+    /// `name_lc` must be lowercase.
+    pub(crate) fn event_target(&self, name_lc: &str) -> Option<(Scopes, Scopes)> {
+        self.event_targets.get(name_lc).copied()
+    }
+
+    pub(crate) fn modifier_exists(&self, name: &str) -> bool {
+        self.modifiers.contains(&name.to_ascii_lowercase())
+    }
+
+    /// Return the root scope of an on-action that is only known from the game data.
+    pub(crate) fn on_action(&self, name: &str) -> Option<Scopes> {
+        self.on_actions.get(name).copied()
+    }
+
+    /// Check if `name` is in the list with the given name, such as `sounds`.
+    /// Returns `None` if the game data has no such list.
+    pub(crate) fn list_contains(&self, list: &str, name: &str) -> Option<bool> {
+        Some(self.lists.get(list)?.contains(&Lowercase::new(name)))
+    }
+
+    /// Return the names in the list with the given name, in sorted order.
+    /// Returns `None` if the game data has no such list.
+    #[cfg(feature = "vic3")]
+    pub(crate) fn list(&self, list: &str) -> Option<Vec<&str>> {
+        use std::borrow::Borrow;
+
+        let mut names: Vec<&str> = self.lists.get(list)?.iter().map(Borrow::borrow).collect();
+        names.sort_unstable();
+        Some(names)
+    }
+
+    /// Return the languages that the game has localization for.
+    /// The list is empty if the game data does not include them.
+    pub(crate) fn languages(&self) -> &[String] {
+        &self.languages
+    }
+}
+
+/// Convert the scope names written by `extract-tables`, falling back to all scopes for names that
+/// this version of tiger does not know.
+fn scopes(token: &Token) -> Scopes {
+    Scopes::from_snake_case_multi(token.as_str()).unwrap_or(Scopes::all())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::parse::pdxfile::parse_pdx_internal;
+
+    fn game_name() -> &'static str {
+        match Game::game() {
+            #[cfg(feature = "ck3")]
+            Game::Ck3 => "ck3",
+            #[cfg(feature = "vic3")]
+            Game::Vic3 => "vic3",
+            #[cfg(feature = "imperator")]
+            Game::Imperator => "imperator",
+        }
+    }
+
+    fn parse(text: String) -> Block {
+        parse_pdx_internal(Box::leak(text.into_boxed_str()), "game_data")
+    }
+
+    #[test]
+    fn test_from_block() {
+        let block = parse(format!(
+            "format_version = {GAME_DATA_FORMAT}
+            game = {}
+            game_version = \"1.2.3\"
+            triggers = {{ New_Trigger = character|province unknown_scope_trigger = not_a_scope }}
+            effects = {{ new_effect = all }}
+            on_actions = {{ on_new_thing = character }}
+            event_targets = {{ new_target = {{ input = character output = province }} }}
+            modifiers = {{ New_Modifier }}
+            lists = {{ sounds = {{ \"event:/SFX/New\" }} map_painting_modes = {{ b a }} }}
+            languages = {{ english klingon }}",
+            game_name()
+        ));
+        let game_data = GameData::from_block(&block).unwrap();
+        assert_eq!(game_data.game_version(), "1.2.3");
+        assert_eq!(
+            game_data.triggers.get("new_trigger"),
+            Some(&(Scopes::Character | Scopes::Province))
+        );
+        assert_eq!(game_data.triggers.get("unknown_scope_trigger"), Some(&Scopes::all()));
+        assert_eq!(game_data.effects.get("new_effect"), Some(&Scopes::all()));
+        assert_eq!(game_data.on_action("on_new_thing"), Some(Scopes::Character));
+        assert_eq!(
+            game_data.event_target("new_target"),
+            Some((Scopes::Character, Scopes::Province))
+        );
+        assert!(game_data.modifier_exists("new_modifier"));
+        assert!(!game_data.modifier_exists("old_modifier"));
+        assert_eq!(game_data.list_contains("sounds", "event:/sfx/new"), Some(true));
+        assert_eq!(game_data.list_contains("sounds", "event:/sfx/old"), Some(false));
+        assert_eq!(game_data.list_contains("music", "event:/sfx/new"), None);
+        #[cfg(feature = "vic3")]
+        assert_eq!(game_data.list("map_painting_modes"), Some(vec!["a", "b"]));
+        assert_eq!(game_data.languages(), ["english", "klingon"]);
+    }
+
+    #[test]
+    fn test_from_block_optional() {
+        let block = parse(format!("format_version = {GAME_DATA_FORMAT}\ngame = {}", game_name()));
+        let game_data = GameData::from_block(&block).unwrap();
+        assert_eq!(game_data.game_version(), "unknown");
+        assert!(game_data.languages().is_empty());
+        assert_eq!(game_data.list_contains("sounds", "event:/sfx/new"), None);
+    }
+
+    #[test]
+    fn test_from_block_mismatch() {
+        let block = parse(format!("format_version = 0\ngame = {}", game_name()));
+        assert!(GameData::from_block(&block).is_err());
+        let block = parse(format!("format_version = {GAME_DATA_FORMAT}\ngame = eu5"));
+        assert!(GameData::from_block(&block).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Detecting which version of the game is installed, and comparing it to the game versions that
+//! the built-in tables were made for.
+//!
+//! When the installed game is newer than a table, reports that depend on that table get a lowered
+//! [`Confidence`], because the game may have added items that the table doesn't know about yet.
+
+use std::cmp::Ordering;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use once_cell::sync::OnceCell;
+
+use crate::game::Game;
+use crate::report::Confidence;
+
+/// The game version detected from the game directory, if any.
+static INSTALLED_VERSION: OnceCell<String> = OnceCell::new();
+
+/// The game version that each group of built-in tables was last updated for.
+///
+/// These correspond to the `LAST UPDATED` markers in the table files. Where a group has several
+/// markers, the oldest one is used.
+#[derive(Debug, Clone, Copy)]
+pub struct TableVersions {
+    pub triggers: &'static str,
+    pub effects: &'static str,
+    pub event_targets: &'static str,
+    pub modifiers: &'static str,
+}
+
+/// The groups of built-in tables that reports can depend on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Table {
+    Triggers,
+    Effects,
+    EventTargets,
+    Modifiers,
+}
+
+impl Table {
+    const ALL: [Table; 4] =
+        [Table::Triggers, Table::Effects, Table::EventTargets, Table::Modifiers];
+
+    fn name(self) -> &'static str {
+        match self {
+            Table::Triggers => "triggers",
+            Table::Effects => "effects",
+            Table::EventTargets => "event targets",
+            Table::Modifiers => "modifiers",
+        }
+    }
+
+    /// The game version this table was last updated for.
+    pub fn version(self) -> &'static str {
+        let versions = match Game::game() {
+            #[cfg(feature = "ck3")]
+            Game::Ck3 => crate::ck3::tables::TABLE_VERSIONS,
+            #[cfg(feature = "vic3")]
+            Game::Vic3 => crate::vic3::tables::TABLE_VERSIONS,
+            #[cfg(feature = "imperator")]
+            Game::Imperator => crate::imperator::tables::TABLE_VERSIONS,
+        };
+        match self {
+            Table::Triggers => versions.triggers,
+            Table::Effects => versions.effects,
+            Table::EventTargets => versions.event_targets,
+            Table::Modifiers => versions.modifiers,
+        }
+    }
+
+    /// Whether the installed game is newer than this table.
+    pub fn is_outdated(self) -> bool {
+        installed_version().is_some_and(|installed| {
+            compare_versions(installed, self.version()) == Ordering::Greater
+        })
+    }
+}
+
+/// Return the confidence for a report that is only valid if all of `tables` are up to date.
+pub fn tables_confidence(tables: &[Table]) -> Confidence {
+    if tables.iter().any(|table| table.is_outdated()) {
+        Confidence::Weak
+    } else {
+        Confidence::Reasonable
+    }
+}
+
+/// Return descriptions of the tables that are older than the installed game, such as
+/// `"triggers (1.14.0.2)"`.
+pub fn outdated_tables() -> Vec<String> {
+    Table::ALL
+        .iter()
+        .filter(|table| table.is_outdated())
+        .map(|table| format!("{} ({})", table.name(), table.version()))
+        .collect()
+}
+
+/// Read the game version from `launcher/launcher-settings.json` in the game directory.
+pub fn detect_game_version(game_dir: &Path) -> Option<String> {
+    let content = read_to_string(game_dir.join("launcher/launcher-settings.json")).ok()?;
+    let settings: serde_json::Value = serde_json::from_str(&content).ok()?;
+    if let Some(version) = settings.get("rawVersion").and_then(|v| v.as_str()) {
+        return Some(version.trim_start_matches('v').to_owned());
+    }
+    // The `version` field may look like `1.14.0.2 (Traveler)`
+    let version = settings.get("version")?.as_str()?;
+    version.split_whitespace().next().map(|v| v.trim_start_matches('v').to_owned())
+}
+
+/// Record which game version is installed. Should be called before validation.
+pub fn set_installed_version(version: String) {
+    _ = INSTALLED_VERSION.set(version);
+}
+
+/// Return the installed game version, if it was detected.
+pub fn installed_version() -> Option<&'static str> {
+    INSTALLED_VERSION.get().map(String::as_str)
+}
+
+/// Compare two dotted version strings numerically, treating missing parts as 0.
+/// Anything after the digits of a part, such as a `-beta` suffix, is ignored.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| -> Vec<u32> {
+        v.split('.')
+            .map(|part| {
+                let digits = part.find(|c: char| !c.is_ascii_digit()).unwrap_or(part.len());
+                part[..digits].parse().unwrap_or(0)
+            })
+            .collect()
+    };
+    let (a, b) = (parse(a), parse(b));
+    for i in 0..a.len().max(b.len()) {
+        let ordering = a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.14.0.2", "1.14.0.2"), Ordering::Equal);
+        assert_eq!(compare_versions("1.14", "1.14.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.14.1", "1.14.0.2"), Ordering::Greater);
+        assert_eq!(compare_versions("1.9.2", "1.10"), Ordering::Less);
+        assert_eq!(compare_versions("2.0.4-beta", "2.0.4"), Ordering::Equal);
+    }
+}
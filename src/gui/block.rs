@@ -1,3 +1,4 @@
+use std::fmt::Write as _;
 use std::sync::Arc;
 
 use crate::block::{Block, BlockItem, Comparator, Eq::Single, Field, BV};
@@ -8,7 +9,7 @@ use crate::gui::{BuiltinWidget, GuiValidation, PropertyContainer, WidgetProperty
 use crate::helpers::{TigerHashMap, TigerHashSet};
 use crate::lowercase::Lowercase;
 use crate::report::{err, untidy, warn, ErrorKey};
-use crate::token::Token;
+use crate::token::{Loc, Token};
 
 /// An element of a [`GuiBlock`]
 #[derive(Debug, Clone)]
@@ -16,12 +17,12 @@ enum GuiItem {
     /// A property assignment.
     Property(WidgetProperty, Token, BV),
     /// A contained widget.
-    Widget(Arc<GuiBlock>),
+    Widget(Token, Arc<GuiBlock>),
     /// A property which contains other properties. It can have `Subst` blocks too.
-    ComplexProperty(Arc<GuiBlock>),
+    ComplexProperty(Token, Arc<GuiBlock>),
     /// A property which contains a widget. It can have Subst blocks too.
     /// Recursive widgets (ones that have `recursive = yes`) are handled as normal `Property` items instead.
-    WidgetProperty(Arc<GuiBlock>),
+    WidgetProperty(Token, Arc<GuiBlock>),
     /// A named block whose contents can be substituted. Will be inlined later.
     Subst(String, Arc<GuiBlock>),
     /// A named block whose contents will be inserted into any Subst of the same name.
@@ -42,6 +43,9 @@ pub struct GuiBlock {
     items: Vec<GuiItem>,
     /// The names of all named blocks in this block, its base types, and its children.
     substnames: TigerHashSet<String>,
+    /// Whether some of the contents could not be resolved, such as unknown types or templates.
+    /// If so, `substnames` may be missing some names.
+    incomplete: bool,
 }
 
 /// An indication of where this [`Block`] was found, to help with determining the metadata for the
@@ -85,7 +89,11 @@ impl GuiBlock {
             base: None,
             items: Vec::new(),
             substnames: TigerHashSet::default(),
+            incomplete: false,
         };
+        // The blockoverrides are applied after all the contents are known, because they can
+        // apply to blocks in widgets that come after them.
+        let mut overrides = Vec::new();
 
         // Fill in `container` and `base` fields if known
         match from {
@@ -95,7 +103,12 @@ impl GuiBlock {
                     gui.container = basetype.builtin(types).map(PropertyContainer::from);
                     let gui_block = basetype.gui_block(types, templates);
                     gui.substnames.clone_from(&gui_block.substnames);
+                    gui.incomplete = gui_block.incomplete;
                     gui.base = Some(gui_block);
+                } else if BuiltinWidget::builtin_current_game(&Lowercase::new(base.as_str()))
+                    .is_none()
+                {
+                    gui.incomplete = true;
                 }
             }
             GuiBlockFrom::PropertyKey(prop) => {
@@ -131,6 +144,7 @@ impl GuiBlock {
                                     gui.inline(&template.gui_block(types, templates));
                                 } else {
                                     untidy(ErrorKey::Gui).msg("template not found").loc(key).push();
+                                    gui.incomplete = true;
                                 }
                             }
                         } else if let Ok(prop) = WidgetProperty::try_from(&key_lc) {
@@ -143,7 +157,8 @@ impl GuiBlock {
                                         types,
                                         templates,
                                     );
-                                    gui.items.push(GuiItem::ComplexProperty(guiblock));
+                                    gui.add_child_names(&guiblock);
+                                    gui.items.push(GuiItem::ComplexProperty(key.clone(), guiblock));
                                 }
                             } else if validation == GuiValidation::Widget {
                                 // If the bv is a Value (should be a template name) or if it is a
@@ -161,9 +176,14 @@ impl GuiBlock {
                                             types,
                                             templates,
                                         );
-                                        gui.items.push(GuiItem::WidgetProperty(guiblock));
+                                        gui.add_child_names(&guiblock);
+                                        gui.items
+                                            .push(GuiItem::WidgetProperty(key.clone(), guiblock));
                                     }
                                     _ => {
+                                        // The contents of recursive widgets are not processed,
+                                        // so any blocks in them are not known.
+                                        gui.incomplete |= bv.get_block().is_some();
                                         gui.items.push(GuiItem::Property(
                                             prop,
                                             key.clone(),
@@ -184,8 +204,8 @@ impl GuiBlock {
                                     types,
                                     templates,
                                 );
-                                gui.substnames.extend(guiblock.substnames.iter().cloned());
-                                gui.items.push(GuiItem::Widget(guiblock));
+                                gui.add_child_names(&guiblock);
+                                gui.items.push(GuiItem::Widget(key.clone(), guiblock));
                             }
                         } else if let Ok(builtin) = BuiltinWidget::try_from(&key_lc) {
                             // If we got here, then it must be a builtin but not for the current game
@@ -228,37 +248,50 @@ impl GuiBlock {
                         let guiblock =
                             GuiBlock::from_block(GuiBlockFrom::NoParent, block, types, templates);
                         gui.substnames.insert(name.to_string());
-                        gui.substnames.extend(guiblock.substnames.iter().cloned());
+                        gui.add_child_names(&guiblock);
                         gui.items.push(GuiItem::Subst(name.to_string(), guiblock));
                     }
                     state = Expecting::Field;
                 }
                 Expecting::BlockOverrideBody(name) => {
                     if let Some(block) = item.expect_block() {
-                        if gui.substnames.contains(name.as_str()) {
-                            let guiblock = GuiBlock::from_block(
-                                GuiBlockFrom::NoParent,
-                                block,
-                                types,
-                                templates,
-                            );
-                            gui.apply_override(name, &guiblock);
-                            gui.items.push(GuiItem::Override(name.clone(), guiblock));
-                        } else if !matches!(from, GuiBlockFrom::Template | GuiBlockFrom::NoParent) {
-                            // TODO: can't do this until we search Widget fields for overrides as well.
-                            // let msg = format!("did not find block for blockoverride `{name}`");
-                            // err(ErrorKey::Gui).msg(msg).loc(name).push();
-                        }
+                        let guiblock =
+                            GuiBlock::from_block(GuiBlockFrom::NoParent, block, types, templates);
+                        overrides.push((name, guiblock));
                     }
                     state = Expecting::Field;
                 }
             }
         }
+
+        for (name, guiblock) in overrides {
+            if gui.substnames.contains(name.as_str()) {
+                gui.apply_override(name, &guiblock);
+                gui.items.push(GuiItem::Override(name.clone(), guiblock));
+            } else if matches!(from, GuiBlockFrom::Template) {
+                // Keep it for when the template is inlined into a widget that has the block.
+                gui.items.push(GuiItem::Override(name.clone(), guiblock));
+            } else if !matches!(from, GuiBlockFrom::NoParent) && !gui.incomplete {
+                let msg = format!("blockoverride `{name}` does not match any block");
+                let info = "the block may have been renamed or removed from the base type";
+                warn(ErrorKey::Gui).msg(msg).info(info).loc(name).push();
+            }
+        }
         Arc::new(gui)
     }
 
+    /// Whether this block, its base types, or its children have a named block `name`.
+    pub fn has_block(&self, name: &str) -> bool {
+        self.substnames.contains(name)
+    }
+
+    fn add_child_names(&mut self, child: &GuiBlock) {
+        self.substnames.extend(child.substnames.iter().cloned());
+        self.incomplete |= child.incomplete;
+    }
+
     pub fn inline(&mut self, other: &Arc<GuiBlock>) {
-        self.substnames.extend(other.substnames.iter().cloned());
+        self.add_child_names(other);
         for item in &other.items {
             if let GuiItem::Override(name, gui_block) = item {
                 self.apply_override(name, gui_block);
@@ -273,7 +306,7 @@ impl GuiBlock {
             return;
         }
 
-        self.substnames.extend(overrideblock.substnames.iter().cloned());
+        self.add_child_names(overrideblock);
 
         if let Some(mut base) = self.base.clone() {
             self.base = Some(Self::apply_override_arc(&mut base, name, overrideblock));
@@ -282,9 +315,9 @@ impl GuiBlock {
         for item in &mut self.items {
             match item {
                 GuiItem::Property(_, _, _) | GuiItem::Override(_, _) => (),
-                GuiItem::Widget(gui)
-                | GuiItem::ComplexProperty(gui)
-                | GuiItem::WidgetProperty(gui) => {
+                GuiItem::Widget(_, gui)
+                | GuiItem::ComplexProperty(_, gui)
+                | GuiItem::WidgetProperty(_, gui) => {
                     *gui = Self::apply_override_arc(gui, name, overrideblock);
                 }
                 GuiItem::Subst(substname, gui) => {
@@ -329,13 +362,98 @@ impl GuiBlock {
                 GuiItem::Subst(_, gui_block) => {
                     gui_block.validate(container, data);
                 }
-                GuiItem::Widget(gui_block)
-                | GuiItem::ComplexProperty(gui_block)
-                | GuiItem::WidgetProperty(gui_block) => {
+                GuiItem::Widget(_, gui_block)
+                | GuiItem::ComplexProperty(_, gui_block)
+                | GuiItem::WidgetProperty(_, gui_block) => {
                     gui_block.validate(None, data);
                 }
                 GuiItem::Override(_, _) => (),
             }
         }
     }
+
+    /// Write the contents of this [`GuiBlock`] as `.gui` script, with its base types expanded and
+    /// templates and blockoverrides already applied.
+    ///
+    /// Every property is annotated with the file and line it came from. Properties from base types
+    /// come first, so a later assignment of the same property is the one that takes effect.
+    pub fn dump(&self, indent: usize, out: &mut String) {
+        if let Some(base) = &self.base {
+            base.dump(indent, out);
+        }
+
+        for item in &self.items {
+            match item {
+                GuiItem::Property(_, key, bv) => {
+                    _ = write!(out, "{:indent$}{key} = ", "", indent = indent * 4);
+                    dump_bv(bv, indent, out);
+                    _ = writeln!(out, "  # {}", describe_loc(key.loc));
+                }
+                GuiItem::Widget(key, gui_block)
+                | GuiItem::ComplexProperty(key, gui_block)
+                | GuiItem::WidgetProperty(key, gui_block) => {
+                    let pad = indent * 4;
+                    _ = writeln!(out, "{:pad$}{key} = {{  # {}", "", describe_loc(key.loc));
+                    gui_block.dump(indent + 1, out);
+                    _ = writeln!(out, "{:pad$}}}", "");
+                }
+                GuiItem::Subst(name, gui_block) => {
+                    _ = writeln!(out, "{:indent$}# block \"{name}\"", "", indent = indent * 4);
+                    gui_block.dump(indent, out);
+                }
+                // Overrides have already been substituted into their blocks.
+                GuiItem::Override(_, _) => (),
+            }
+        }
+    }
+}
+
+/// Write a property value as it would appear in a `.gui` file.
+/// Blocks are written over multiple lines, ending without a newline.
+fn dump_bv(bv: &BV, indent: usize, out: &mut String) {
+    match bv {
+        BV::Value(token) => dump_value(token, out),
+        BV::Block(block) => dump_block(block, indent, out),
+    }
+}
+
+fn dump_block(block: &Block, indent: usize, out: &mut String) {
+    // Short lists of values, such as sizes and colors, are kept on one line
+    if block.iter_items().all(|item| matches!(item, BlockItem::Value(_))) {
+        _ = write!(out, "{{");
+        for token in block.iter_values() {
+            _ = write!(out, " ");
+            dump_value(token, out);
+        }
+        _ = write!(out, " }}");
+        return;
+    }
+
+    _ = writeln!(out, "{{");
+    for item in block.iter_items() {
+        _ = write!(out, "{:indent$}", "", indent = (indent + 1) * 4);
+        match item {
+            BlockItem::Value(token) => dump_value(token, out),
+            BlockItem::Block(block) => dump_block(block, indent + 1, out),
+            BlockItem::Field(Field(key, cmp, bv)) => {
+                _ = write!(out, "{key} {cmp} ");
+                dump_bv(bv, indent + 1, out);
+            }
+        }
+        _ = writeln!(out);
+    }
+    _ = write!(out, "{:indent$}}}", "", indent = indent * 4);
+}
+
+fn dump_value(token: &Token, out: &mut String) {
+    let s = token.as_str();
+    if s.is_empty() || s.contains(|c: char| c.is_whitespace() || c == '"' || c == '#') {
+        _ = write!(out, "\"{}\"", s.replace('"', "\\\""));
+    } else {
+        _ = write!(out, "{s}");
+    }
+}
+
+fn describe_loc(loc: Loc) -> String {
+    format!("{}:{}", loc.pathname().display(), loc.line)
 }
@@ -1,3 +1,5 @@
+use crate::game_version::TableVersions;
+
 pub mod datafunctions;
 pub mod effects;
 pub mod localization;
@@ -6,3 +8,12 @@ pub mod modifs;
 pub mod on_action;
 pub mod sounds;
 pub mod triggers;
+
+/// The game versions that the tables were last updated for.
+/// Keep these in sync with the `LAST UPDATED` markers when updating the tables.
+pub const TABLE_VERSIONS: TableVersions = TableVersions {
+    triggers: "2.0.4",
+    effects: "2.0.4",
+    event_targets: "2.0.4",
+    modifiers: "2.0.4",
+};
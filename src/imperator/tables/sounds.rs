@@ -4,6 +4,7 @@ use crate::helpers::TigerHashSet;
 use crate::lowercase::Lowercase;
 
 /// A hashed version of [`SOUNDS`], for quick case-insensitive lookup.
+/// Only used when the game's own `sound/GUIDs.txt` could not be loaded.
 pub static SOUNDS_SET: Lazy<TigerHashSet<Lowercase<'static>>> = Lazy::new(|| {
     let mut set = TigerHashSet::default();
     for sound in SOUNDS.iter().copied() {
@@ -12,6 +12,10 @@ pub use crate::config_load::validate_config_file;
 pub use crate::everything::Everything;
 pub use crate::fileset::FileKind;
 pub use crate::game::Game;
+pub use crate::game_data::GameData;
+pub use crate::game_version::{
+    detect_game_version, installed_version, outdated_tables, set_installed_version,
+};
 pub use crate::item::Item;
 #[cfg(feature = "vic3")]
 pub use crate::mod_metadata::ModMetadata;
@@ -40,32 +44,43 @@ mod date;
 mod db;
 mod dds;
 mod desc;
+mod drift;
 mod effect;
 mod effect_validation;
 mod everything;
+mod expand;
 mod fileset;
+mod font;
 mod game;
+mod game_data;
+mod game_version;
 mod gui;
 mod helpers;
 mod item;
 mod lowercase;
 mod macros;
+mod mesh;
 #[cfg(feature = "vic3")]
 mod mod_metadata;
 #[cfg(any(feature = "ck3", feature = "imperator"))]
 mod modfile;
 mod modif;
 mod on_action;
+mod overrides;
 mod parse;
 mod pathtable;
 mod pdxfile;
+mod perf;
 mod report;
 mod rivers;
 mod scopes;
 mod script_value;
+mod sound;
 mod token;
 mod tooltipped;
 mod trigger;
+mod trigger_logic;
 mod util;
 mod validate;
 mod validator;
+mod value_range;
@@ -6,9 +6,77 @@ use std::sync::RwLock;
 
 use once_cell::sync::Lazy;
 
-use crate::helpers::{BiTigerHashMap, TigerHashMap};
+use crate::block::{Block, BV};
+use crate::context::ScopeContext;
+use crate::everything::Everything;
+use crate::helpers::{edit_distance, stringify_list, BiTigerHashMap, TigerHashMap};
+use crate::report::{err, fatal, warn, ErrorKey, Severity};
+use crate::scopes::{scope_prefix, scope_to_scope, Scopes};
 use crate::token::{Loc, Token};
 use crate::tooltipped::Tooltipped;
+use crate::validator::Validator;
+
+/// Collect the macro arguments of a call to a scripted trigger or effect.
+/// Parameters that the call doesn't supply are left out.
+pub fn macro_args(parms: &[&'static str], bv: &BV) -> Vec<(&'static str, Token)> {
+    let Some(block) = bv.get_block() else {
+        return Vec::new();
+    };
+    parms
+        .iter()
+        .filter_map(|parm| block.get_field_value(parm).map(|value| (*parm, value.clone())))
+        .collect()
+}
+
+/// Check the arguments of a call to a scripted trigger or effect against the parameters that its
+/// definition uses. `what` is "scripted trigger" or "scripted effect".
+///
+/// All missing parameters are reported together, and unneeded arguments whose names are close to
+/// a missing parameter are pointed out as likely typos.
+/// Returns the arguments in the order of `parms`, or `None` if any are missing.
+pub fn validate_macro_args(
+    what: &str,
+    parms: &[&'static str],
+    block: &Block,
+    data: &Everything,
+    max_sev: Severity,
+) -> Option<Vec<(&'static str, Token)>> {
+    let mut args = Vec::new();
+    let mut missing = Vec::new();
+    let mut vd = Validator::new(block, data);
+    vd.set_max_severity(max_sev);
+    for parm in parms {
+        if let Some(token) = vd.field_value(parm) {
+            args.push((*parm, token.clone()));
+        } else {
+            missing.push(*parm);
+        }
+    }
+    vd.unknown_value_fields(|key, _value| {
+        let msg = format!("this {what} does not need parameter {key}");
+        let info = if let Some(parm) = missing.iter().find(|parm| is_typo(key.as_str(), parm)) {
+            format!("did you mean {parm}?")
+        } else {
+            "supplying an unneeded parameter often causes a crash".to_owned()
+        };
+        fatal(ErrorKey::Macro).msg(msg).info(info).loc(key).push();
+    });
+
+    if missing.is_empty() {
+        Some(args)
+    } else {
+        let s = if missing.len() == 1 { "" } else { "s" };
+        let msg = format!("this {what} needs parameter{s} {}", stringify_list(&missing));
+        err(ErrorKey::Macro).msg(msg).loc(block).push();
+        None
+    }
+}
+
+/// Guess whether `arg` is a misspelling of the parameter `parm`.
+/// Allow about one mistake for every three letters.
+fn is_typo(arg: &str, parm: &str) -> bool {
+    3 * edit_distance(arg, parm) <= parm.len().max(3)
+}
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 struct MacroKey {
@@ -81,6 +149,117 @@ impl<T> Default for MacroCache<T> {
     }
 }
 
+/// A record of how a scripted trigger or effect is called, so that its call sites can be compared
+/// with each other once everything has been validated.
+#[derive(Debug, Default)]
+pub struct CallRecord {
+    inner: RwLock<CallRecordInner>,
+}
+
+#[derive(Debug, Default)]
+struct CallRecordInner {
+    /// The first call that supplied macro arguments.
+    with_args: Option<Token>,
+    /// The first call that was just `= yes` or `= no`.
+    without_args: Option<Token>,
+    /// For each parameter, the first argument seen for each combination of scope types.
+    arg_scopes: TigerHashMap<&'static str, Vec<(Scopes, Token)>>,
+}
+
+impl CallRecord {
+    pub fn record(
+        &self,
+        key: &Token,
+        bv: &BV,
+        args: &[(&'static str, Token)],
+        sc: &mut ScopeContext,
+    ) {
+        let scopes: Vec<_> = args
+            .iter()
+            .filter_map(|(parm, arg)| arg_scopes(arg, sc).map(|scopes| (*parm, scopes, arg)))
+            .collect();
+        let mut inner = self.inner.write().unwrap();
+        // Keep the earliest call sites, so that the reports don't depend on validation order.
+        let first =
+            if bv.get_block().is_some() { &mut inner.with_args } else { &mut inner.without_args };
+        if first.as_ref().map_or(true, |first| key.loc < first.loc) {
+            *first = Some(key.clone());
+        }
+        for (parm, scopes, arg) in scopes {
+            let seen = inner.arg_scopes.entry(parm).or_default();
+            if let Some((_, token)) = seen.iter_mut().find(|(s, _)| *s == scopes) {
+                if arg.loc < token.loc {
+                    token.clone_from(arg);
+                }
+            } else {
+                seen.push((scopes, arg.clone()));
+            }
+        }
+    }
+
+    /// Report definitions that are called both with and without macro arguments, and parameters
+    /// that get arguments of incompatible scope types at different call sites.
+    /// `what` is "scripted trigger" or "scripted effect".
+    pub fn check(&self, definition: &Token, what: &str) {
+        let inner = self.inner.read().unwrap();
+        if let (Some(with_args), Some(without_args)) = (&inner.with_args, &inner.without_args) {
+            let msg = format!("{what} `{definition}` is called both with and without parameters");
+            warn(ErrorKey::Macro)
+                .msg(msg)
+                .loc(definition)
+                .loc_msg(with_args, "called with parameters here")
+                .loc_msg(without_args, "called without parameters here")
+                .push();
+        }
+
+        let mut parms: Vec<_> = inner.arg_scopes.iter().collect();
+        parms.sort_unstable_by_key(|(parm, _)| **parm);
+        for (parm, seen) in parms {
+            let mut seen = seen.clone();
+            seen.sort_unstable_by_key(|(_, token)| token.loc);
+            let conflict = seen.iter().enumerate().find_map(|(i, (scopes, token))| {
+                seen[i + 1..]
+                    .iter()
+                    .find(|(other, _)| !scopes.intersects(*other))
+                    .map(|other| (scopes, token, other))
+            });
+            if let Some((scopes, token, (other_scopes, other))) = conflict {
+                let msg = format!(
+                    "parameter ${parm}$ of {what} `{definition}` is given {other_scopes} here"
+                );
+                let msg2 = format!("but {scopes} here");
+                warn(ErrorKey::Scopes).msg(msg).loc(other).loc_msg(token, msg2).push();
+            }
+        }
+    }
+}
+
+/// Return the scope types that a macro argument produces when it's used as a target, if it looks
+/// like a target at all. Unlike [`validate_target`](crate::trigger::validate_target), this
+/// doesn't report anything, because the argument is validated where it's used in the expansion.
+fn arg_scopes(arg: &Token, sc: &mut ScopeContext) -> Option<Scopes> {
+    if arg.is_number() {
+        return Some(Scopes::Value);
+    }
+    let mut scopes = sc.scopes();
+    for (i, part) in arg.split('.').iter().enumerate() {
+        if let Some((prefix, name)) = part.split_once(':') {
+            if prefix.lowercase_is("scope") {
+                scopes = sc.is_name_defined(name.as_str())?;
+            } else {
+                scopes = scope_prefix(&prefix)?.1;
+            }
+        } else if i == 0 && part.lowercase_is("root") {
+            scopes = sc.root_scopes();
+        } else if i == 0 && part.lowercase_is("this") {
+            scopes = sc.scopes();
+        } else {
+            scopes = scope_to_scope(part, scopes)?.1;
+        }
+    }
+    (!scopes.is_empty() && scopes != Scopes::all()).then_some(scopes)
+}
+
 /// Global macro map
 pub(crate) static MACRO_MAP: Lazy<MacroMap> = Lazy::new(MacroMap::default);
 
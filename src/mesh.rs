@@ -0,0 +1,210 @@
+//! Reader for the binary `.mesh` and `.anim` files that are referenced from `.asset` files.
+//!
+//! Both use the same generic format: a `@@b@` magic number followed by a tree of named objects
+//! with typed properties. Only the object names are kept, which is enough to know which shapes,
+//! bones and locators a mesh provides.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use crate::fileset::FileEntry;
+use crate::helpers::TigerHashMap;
+use crate::report::{err, ErrorKey};
+
+const MAGIC: &[u8] = b"@@b@";
+
+/// The object names found in a `.mesh` file.
+#[derive(Clone, Debug, Default)]
+pub struct MeshInfo {
+    /// The shapes, which are the names used in `meshsettings` and `blend_shape` blocks.
+    shapes: Vec<String>,
+    /// The skeleton bones of all the shapes.
+    bones: Vec<String>,
+    locators: Vec<String>,
+}
+
+impl MeshInfo {
+    fn from_objects(objects: Vec<(usize, String)>) -> Self {
+        let mut info = MeshInfo::default();
+        // The names of the objects currently being visited, by depth
+        let mut path: Vec<String> = Vec::new();
+        for (depth, name) in objects {
+            path.truncate(depth - 1);
+            let parent = |d: usize| path.get(d).map(String::as_str);
+            match (depth, parent(0), parent(2)) {
+                (2, Some("object"), _) => info.shapes.push(name.clone()),
+                (2, Some("locator"), _) => info.locators.push(name.clone()),
+                (4, Some("object"), Some("skeleton")) => info.bones.push(name.clone()),
+                _ => (),
+            }
+            path.push(name);
+        }
+        info
+    }
+
+    pub fn has_shape(&self, name: &str) -> bool {
+        self.shapes.iter().any(|shape| shape.eq_ignore_ascii_case(name))
+    }
+
+    pub fn has_bone_or_locator(&self, name: &str) -> bool {
+        self.bones.iter().chain(&self.locators).any(|bone| bone.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Parsed `.mesh` and `.anim` files, loaded on demand because the base game has a great many of
+/// them and only the ones referenced by the mod's assets need to be looked at.
+#[derive(Debug, Default)]
+pub struct MeshFiles {
+    cache: RwLock<TigerHashMap<PathBuf, Option<Arc<MeshInfo>>>>,
+}
+
+impl MeshFiles {
+    /// Return the contents of the file, or `None` if it could not be read.
+    /// Problems with the file are reported the first time it is looked at.
+    pub fn get(&self, entry: &FileEntry) -> Option<Arc<MeshInfo>> {
+        if let Some(info) = self.cache.read().unwrap().get(entry.path()) {
+            return info.clone();
+        }
+
+        let mut cache = self.cache.write().unwrap();
+        // Another thread may have loaded it in the meantime
+        if let Some(info) = cache.get(entry.path()) {
+            return info.clone();
+        }
+        let info = match File::open(entry.fullpath()).and_then(|f| read_objects(BufReader::new(f)))
+        {
+            Ok(objects) => Some(Arc::new(MeshInfo::from_objects(objects))),
+            Err(e) => {
+                let msg = "could not read binary mesh data";
+                let info = format!("{e:#}");
+                err(ErrorKey::ReadError).msg(msg).info(info).loc(entry).push();
+                None
+            }
+        };
+        cache.insert(entry.path().to_path_buf(), info.clone());
+        info
+    }
+
+    /// Report problems with the file without looking at its contents.
+    pub fn validate(&self, entry: &FileEntry) {
+        self.get(entry);
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_u8<R: Read>(f: &mut R) -> io::Result<u8> {
+    let mut buffer = [0; 1];
+    f.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+fn read_u32<R: Read>(f: &mut R) -> io::Result<u32> {
+    let mut buffer = [0; 4];
+    f.read_exact(&mut buffer)?;
+    Ok(u32::from_le_bytes(buffer))
+}
+
+fn skip<R: Read>(f: &mut R, amount: u64) -> io::Result<()> {
+    let skipped = io::copy(&mut f.take(amount), &mut io::sink())?;
+    if skipped < amount {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+/// Read the objects in the file and return them in order, along with their depth in the tree.
+///
+/// Objects are introduced by one `[` per level of depth, followed by a nul-terminated name.
+/// Properties are introduced by `!` and have a name, a type letter, a count, and the values.
+fn read_objects<R: BufRead>(mut f: R) -> io::Result<Vec<(usize, String)>> {
+    let mut magic = [0; 4];
+    f.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(invalid("file does not start with `@@b@`".to_string()));
+    }
+
+    let mut objects = Vec::new();
+    while let Some(&next) = f.fill_buf()?.first() {
+        match next {
+            b'[' => {
+                let mut depth = 0;
+                while f.fill_buf()?.first() == Some(&b'[') {
+                    f.consume(1);
+                    depth += 1;
+                }
+                if depth > objects.last().map_or(0, |(d, _)| *d) + 1 {
+                    return Err(invalid(format!("object nesting jumps to depth {depth}")));
+                }
+                let mut name = Vec::new();
+                f.read_until(0, &mut name)?;
+                if name.pop() != Some(0) {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                objects.push((depth, String::from_utf8_lossy(&name).into_owned()));
+            }
+            b'!' => {
+                f.consume(1);
+                let len = read_u8(&mut f)?;
+                skip(&mut f, u64::from(len))?;
+                let datatype = read_u8(&mut f)?;
+                let count = read_u32(&mut f)?;
+                match datatype {
+                    b'i' | b'f' => skip(&mut f, u64::from(count) * 4)?,
+                    b's' => {
+                        for _ in 0..count {
+                            let len = read_u32(&mut f)?;
+                            skip(&mut f, u64::from(len))?;
+                        }
+                    }
+                    _ => {
+                        let msg = format!("unknown property type {datatype:#04x}");
+                        return Err(invalid(msg));
+                    }
+                }
+            }
+            _ => return Err(invalid(format!("unexpected byte {next:#04x}"))),
+        }
+    }
+    Ok(objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(out: &mut Vec<u8>, name: &str, datatype: u8, data: &[u8]) {
+        out.push(b'!');
+        out.push(u8::try_from(name.len()).unwrap());
+        out.extend(name.as_bytes());
+        out.push(datatype);
+        out.extend(1u32.to_le_bytes());
+        out.extend(data);
+    }
+
+    #[test]
+    fn test_read_mesh() {
+        let mut data = MAGIC.to_vec();
+        property(&mut data, "pdxasset", b'i', &1u32.to_le_bytes());
+        data.extend(b"[object\0[[bodyShape\0[[[mesh\0");
+        property(&mut data, "p", b'f', &1.0f32.to_le_bytes());
+        data.extend(b"[[[[material\0");
+        property(&mut data, "shader", b's', b"\x08\0\0\0PdxMesh\0");
+        data.extend(b"[[[skeleton\0[[[[Root\0[[[[Head\0[locator\0[[Hand_L\0");
+
+        let objects = read_objects(data.as_slice()).unwrap();
+        let info = MeshInfo::from_objects(objects);
+        assert_eq!(info.shapes, vec!["bodyShape"]);
+        assert_eq!(info.bones, vec!["Root", "Head"]);
+        assert_eq!(info.locators, vec!["Hand_L"]);
+        assert!(info.has_bone_or_locator("hand_l"));
+
+        data.truncate(data.len() - 3);
+        data.extend(b"?");
+        assert!(read_objects(data.as_slice()).is_err());
+    }
+}
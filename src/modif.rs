@@ -9,9 +9,11 @@ use bitflags::bitflags;
 use crate::block::Block;
 use crate::everything::Everything;
 use crate::game::Game;
+use crate::game_data::GameData;
+use crate::game_version::{tables_confidence, Table};
 #[cfg(any(feature = "ck3", feature = "vic3"))]
 use crate::item::Item;
-use crate::report::{err, ErrorKey, Severity};
+use crate::report::{err, untidy, warn, ErrorKey, Severity};
 use crate::script_value::validate_non_dynamic_script_value;
 use crate::token::Token;
 use crate::validator::Validator;
@@ -101,9 +103,14 @@ pub fn validate_modifs<'a>(
     };
 
     vd.unknown_fields(|key, bv| {
-        if let Some(mk) = lookup_modif(key, data, Some(Severity::Error)) {
+        if let Some(mk) =
+            lookup_modif(key, data, Some(Severity::Error)).or_else(|| game_data_modif(key))
+        {
             kinds.require(mk, key);
             validate_non_dynamic_script_value(bv, data);
+            if let Some(value) = bv.get_value() {
+                validate_modif_value(key, value, data);
+            }
             #[cfg(feature = "ck3")]
             if Game::is_ck3()
                 && !key.is("health")
@@ -123,11 +130,115 @@ pub fn validate_modifs<'a>(
             }
         } else {
             let msg = format!("unknown modifier `{key}`");
-            err(ErrorKey::UnknownField).msg(msg).loc(key).push();
+            let conf = tables_confidence(&[Table::Modifiers]);
+            err(ErrorKey::UnknownField).conf(conf).msg(msg).loc(key).push();
         }
     });
 }
 
+/// How the game interprets the value of a modif.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ModifValue {
+    /// The modif is either on or off, such as vic3's `_bool` modifs.
+    Boolean,
+    /// The value is a fraction that is shown as a percentage, so 0.5 means +50%.
+    Percentage,
+    Other,
+}
+
+impl ModifValue {
+    /// Guess how the value of the modif `key` is interpreted, from its format definition if the
+    /// game has those, or else from its name.
+    #[allow(unused_variables)] // data is only used for ck3 and vic3
+    pub fn of(key: &Token, data: &Everything) -> Self {
+        #[cfg(feature = "ck3")]
+        if Game::is_ck3() {
+            if let Some((_, block)) = data.get_key_block(Item::ModifierFormat, key.as_str()) {
+                if block.get_field_bool("already_percent").unwrap_or(false) {
+                    return ModifValue::Other;
+                } else if block.get_field_bool("percent").unwrap_or(false) {
+                    return ModifValue::Percentage;
+                }
+            }
+        }
+        #[cfg(feature = "vic3")]
+        if Game::is_vic3() {
+            if let Some((_, block)) = data.get_key_block(Item::ModifierTypeDefinition, key.as_str())
+            {
+                if block.get_field_bool("boolean").unwrap_or(false) {
+                    return ModifValue::Boolean;
+                } else if block.get_field_bool("percent").unwrap_or(false) {
+                    return ModifValue::Percentage;
+                }
+            }
+        }
+        Self::from_name(key.as_str())
+    }
+
+    /// Guess how the value of a modif is interpreted from just its name.
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        if name.ends_with("_bool") || name.contains("_enabled") || name.starts_with("can_") {
+            ModifValue::Boolean
+        } else if name.ends_with("_mult") {
+            ModifValue::Percentage
+        } else {
+            ModifValue::Other
+        }
+    }
+}
+
+/// Check that the format definition of a modif agrees with its name and its localization.
+/// `loca` is the localization key of the modif's name.
+#[cfg(any(feature = "ck3", feature = "vic3"))]
+pub fn validate_modif_format(key: &Token, block: &Block, loca: &str, data: &Everything) {
+    if block.get_field_bool("already_percent").unwrap_or(false) {
+        return;
+    }
+    let percent = block.get_field_bool("percent").unwrap_or(false);
+    if !percent && ModifValue::from_name(key.as_str()) == ModifValue::Percentage {
+        let msg = format!("`{key}` is a multiplier but is not shown as a percentage");
+        let info = "with `percent = yes`, a value of 0.1 is shown as +10%";
+        warn(ErrorKey::Validation).msg(msg).info(info).loc(key).push();
+    }
+    if percent {
+        if let Some(text) =
+            data.localization.get_text(loca).filter(|text| text.as_str().contains('%'))
+        {
+            let msg = format!("localization of `{key}` has a % sign");
+            let info = "the game already adds one when showing percentage modifiers";
+            warn(ErrorKey::Localization).msg(msg).info(info).loc(text).push();
+        }
+    }
+}
+
+/// Check that a modif's value makes sense for the kind of modif it is.
+fn validate_modif_value(key: &Token, value: &Token, data: &Everything) {
+    let kind = ModifValue::of(key, data);
+    let number = value.get_number();
+    if value.is("no") || number == Some(0.0) {
+        let msg = format!("`{key} = {value}` has no effect");
+        untidy(ErrorKey::Logic).msg(msg).loc(value).push();
+    } else if kind == ModifValue::Boolean {
+        if !value.is("yes") && number != Some(1.0) {
+            let msg = format!("`{key}` is a yes or no modifier");
+            let info = "use `yes`, or 1";
+            warn(ErrorKey::Validation).msg(msg).info(info).loc(value).push();
+        }
+    } else if let Some(n) = number {
+        if kind == ModifValue::Percentage && n.abs() > 10.0 {
+            let msg = format!("`{key}` is a percentage, so {value} means {:+}%", n * 100.0);
+            let info = format!("if you meant {value}%, write {}", n / 100.0);
+            warn(ErrorKey::Range).msg(msg).info(info).loc(value).push();
+        }
+    }
+}
+
+/// Accept modifs that are only known from the game data, without knowing what they apply to.
+fn game_data_modif(key: &Token) -> Option<ModifKinds> {
+    GameData::get()?.modifier_exists(key.as_str()).then(ModifKinds::all)
+}
+
 #[cfg(any(feature = "ck3", feature = "vic3"))]
 pub fn verify_modif_exists(key: &Token, data: &Everything, kinds: ModifKinds, sev: Severity) {
     let lookup_modif = match Game::game() {
@@ -137,10 +248,11 @@ pub fn verify_modif_exists(key: &Token, data: &Everything, kinds: ModifKinds, se
         Game::Vic3 => crate::vic3::tables::modifs::lookup_modif,
     };
 
-    if let Some(mk) = lookup_modif(key, data, Some(sev)) {
+    if let Some(mk) = lookup_modif(key, data, Some(sev)).or_else(|| game_data_modif(key)) {
         kinds.require(mk, key);
     } else {
         let msg = format!("unknown modifier `{key}`");
-        err(ErrorKey::UnknownField).msg(msg).loc(key).push();
+        let conf = tables_confidence(&[Table::Modifiers]);
+        err(ErrorKey::UnknownField).conf(conf).msg(msg).loc(key).push();
     }
 }
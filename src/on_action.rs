@@ -9,6 +9,7 @@ use crate::block::BV;
 use crate::context::ScopeContext;
 use crate::everything::Everything;
 use crate::game::Game;
+use crate::game_data::GameData;
 use crate::helpers::TigerHashMap;
 #[cfg(feature = "ck3")]
 use crate::item::Item;
@@ -47,6 +48,13 @@ pub fn on_action_scopecontext(key: &Token, data: &Everything) -> Option<ScopeCon
         return Some(sc);
     }
 
+    if let Some(root) = GameData::get().and_then(|game_data| game_data.on_action(key.as_str())) {
+        // Only the root scope is known for on-actions from the game data
+        let mut sc = ScopeContext::new(root, key);
+        sc.set_strict_scopes(false);
+        return Some(sc);
+    }
+
     #[cfg(feature = "ck3")]
     if Game::is_ck3() {
         if let Some(relation) = key.as_str().strip_suffix("_quarterly_pulse") {
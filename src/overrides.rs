@@ -0,0 +1,292 @@
+//! Finding mod files that replace a base game file with a full copy of it, while changing only a
+//! few of the items in it. Such copies silently go stale when the game is updated.
+
+use std::path::Path;
+
+use crate::block::{Block, BlockItem, Field};
+use crate::everything::Everything;
+use crate::fileset::FileEntry;
+use crate::game::Game;
+use crate::helpers::TigerHashMap;
+use crate::item::ItemLoader;
+use crate::pdxfile::PdxFile;
+use crate::report::{tips, ErrorKey};
+use crate::token::Token;
+
+/// Key prefixes that tell the game to change an existing database item rather than define a new
+/// one. Longer prefixes come first so that they are matched before their shorter forms.
+const ITEM_PREFIXES: &[&str] = &[
+    "INJECT_OR_CREATE:",
+    "REPLACE_OR_CREATE:",
+    "TRY_INJECT:",
+    "TRY_REPLACE:",
+    "INJECT:",
+    "REPLACE:",
+];
+
+/// How the game resolves two definitions of the same item. This decides how a mod can override
+/// just some of the items in a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OverrideStyle {
+    /// The definition loaded last wins, so the changed items go in a file that sorts after the
+    /// base game's file.
+    LastWins,
+    /// The definition loaded first wins, so the changed items go in a file that sorts before the
+    /// base game's file. This is how gui types and templates work.
+    FirstWins,
+    /// Items can be overridden from any file by writing `REPLACE:` before their names, or
+    /// changed in place with `INJECT:`.
+    Prefixed,
+}
+
+/// The items defined in a file, by name, each with the parts of the file that make it up.
+type Definitions<'a> = TigerHashMap<String, (&'a Token, Vec<&'a BlockItem>)>;
+
+/// Report the mod files that replace a base game file but change at most a quarter of its items,
+/// and list the items that do differ.
+pub fn check_overrides(data: &Everything) {
+    for (vanilla, mine) in data.fileset.overridden_files() {
+        let filename = mine.filename().to_string_lossy();
+        let is_gui = filename.ends_with(".gui");
+        if !is_gui && !filename.ends_with(".txt") {
+            continue;
+        }
+        let loader = item_loader(mine.path());
+        let (old, new) = if let Some(loader) = loader {
+            (
+                PdxFile::read_encoded(vanilla, loader.encoding(), &data.parser),
+                PdxFile::read_encoded(mine, loader.encoding(), &data.parser),
+            )
+        } else {
+            (
+                PdxFile::read_optional_bom(vanilla, &data.parser),
+                PdxFile::read_optional_bom(mine, &data.parser),
+            )
+        };
+        let (Some(old), Some(new)) = (old, new) else {
+            continue;
+        };
+        let (Some(old_items), Some(new_items)) =
+            (definitions(&old, is_gui), definitions(&new, is_gui))
+        else {
+            continue;
+        };
+        let Some(changed) = changed_items(&old_items, &new_items) else {
+            continue;
+        };
+        if changed.len() * 4 > new_items.len() {
+            continue;
+        }
+
+        let style = if is_gui {
+            Some(OverrideStyle::FirstWins)
+        } else if loader.is_some() {
+            if Game::is_vic3() {
+                Some(OverrideStyle::Prefixed)
+            } else {
+                Some(OverrideStyle::LastWins)
+            }
+        } else {
+            None
+        };
+        report(mine, &changed, new_items.len(), style);
+    }
+}
+
+/// Find the loader for the database items in the file at `path`, if it holds such items.
+fn item_loader(path: &Path) -> Option<&'static ItemLoader> {
+    inventory::iter::<ItemLoader>.into_iter().find(|loader| {
+        loader.for_game(Game::game())
+            && !loader.whole_file()
+            && loader.extension() == ".txt"
+            && path.starts_with(loader.itype().path())
+    })
+}
+
+/// The name of a database item, without any `REPLACE:` or `INJECT:` style prefix.
+fn item_name(key: &str) -> &str {
+    ITEM_PREFIXES.iter().find_map(|prefix| key.strip_prefix(prefix)).unwrap_or(key)
+}
+
+/// Collect the items defined in a file. Returns `None` if the file has parts that aren't items,
+/// or defines an item more than once, because then it can't be compared item by item.
+///
+/// For gui files, the items are the templates and the types inside `types` blocks. Other files
+/// are lists of `key = value` items.
+fn definitions(block: &Block, is_gui: bool) -> Option<Definitions<'_>> {
+    let mut definitions = Definitions::default();
+    let mut add = |name: String, key, parts| definitions.insert(name, (key, parts)).is_none();
+    if !is_gui {
+        for item in block.iter_items() {
+            let BlockItem::Field(Field(key, _, _)) = item else {
+                return None;
+            };
+            if !add(item_name(key.as_str()).to_owned(), key, vec![item]) {
+                return None;
+            }
+        }
+        return Some(definitions);
+    }
+
+    let items = block.iter_items().as_slice();
+    let mut i = 0;
+    while i < items.len() {
+        let (
+            Some(BlockItem::Value(kw)),
+            Some(BlockItem::Value(name)),
+            Some(body @ BlockItem::Block(types)),
+        ) = (items.get(i), items.get(i + 1), items.get(i + 2))
+        else {
+            // Top-level widgets and layers can only be changed by replacing the whole file.
+            return None;
+        };
+        if kw.lowercase_is("template") || kw.lowercase_is("local_template") {
+            if !add(format!("template {name}"), name, vec![body]) {
+                return None;
+            }
+        } else if kw.lowercase_is("types") {
+            let inner = types.iter_items().as_slice();
+            let mut j = 0;
+            while j < inner.len() {
+                let (
+                    Some(BlockItem::Value(kw)),
+                    Some(def @ BlockItem::Field(Field(key, _, _))),
+                    Some(body @ BlockItem::Block(_)),
+                ) = (inner.get(j), inner.get(j + 1), inner.get(j + 2))
+                else {
+                    return None;
+                };
+                if !(kw.lowercase_is("type") || kw.lowercase_is("local_type"))
+                    || !add(format!("type {key}"), key, vec![def, body])
+                {
+                    return None;
+                }
+                j += 3;
+            }
+        } else {
+            return None;
+        }
+        i += 3;
+    }
+    Some(definitions)
+}
+
+/// Return the items in `new` that are new or different from their version in `old`, in file
+/// order. Returns `None` if `new` lacks some of the items in `old`, because removing a base game
+/// item is only possible by replacing the whole file.
+fn changed_items<'a>(old: &Definitions, new: &Definitions<'a>) -> Option<Vec<&'a Token>> {
+    if old.keys().any(|name| !new.contains_key(name)) {
+        return None;
+    }
+    let mut changed: Vec<_> = new
+        .iter()
+        .filter(|(name, (_, parts))| {
+            old.get(*name).map_or(true, |(_, old_parts)| {
+                old_parts.len() != parts.len()
+                    || old_parts.iter().zip(parts).any(|(a, b)| !a.equivalent(b))
+            })
+        })
+        .map(|(_, (key, _))| *key)
+        .collect();
+    changed.sort_unstable_by_key(|key| key.loc);
+    Some(changed)
+}
+
+/// Explain how to override only the changed items, given how the game resolves duplicates.
+fn advice(style: Option<OverrideStyle>, path: &Path) -> String {
+    let dir = path.parent().map_or(String::new(), |dir| format!("{}/", dir.display()));
+    let filename = path.file_name().map_or(String::new(), |name| name.to_string_lossy().into());
+    match style {
+        Some(OverrideStyle::LastWins) => format!("to keep up with game updates more easily, put only these items in a file with a name that sorts after it, such as {dir}zz_{filename}"),
+        Some(OverrideStyle::FirstWins) => format!("to keep up with game updates more easily, put only these items in a file with a name that sorts before it, such as {dir}00_{filename}"),
+        Some(OverrideStyle::Prefixed) => "to keep up with game updates more easily, put only these items in a file with a different name, with `REPLACE:` before their names, or use `INJECT:` to change only some of their fields".to_owned(),
+        None => "a copy of the whole file goes stale when the game is updated".to_owned(),
+    }
+}
+
+fn report(mine: &FileEntry, changed: &[&Token], total: usize, style: Option<OverrideStyle>) {
+    if changed.is_empty() {
+        let msg = "this file is the same as the base game file it replaces";
+        let info = "it can be removed";
+        tips(ErrorKey::FileOverride).msg(msg).info(info).loc(mine).push();
+        return;
+    }
+    let names: Vec<_> = changed.iter().map(|key| format!("`{key}`")).collect();
+    let msg = format!(
+        "this file replaces the base game file but changes only {} of its {total} items: {}",
+        changed.len(),
+        names.join(", ")
+    );
+    let info = advice(style, mine.path());
+    let mut builder = tips(ErrorKey::FileOverride).msg(msg).info(info).loc(mine);
+    for key in changed {
+        builder = builder.loc_msg(*key, "changed");
+    }
+    builder.push();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::parse::pdxfile::parse_pdx_internal;
+
+    fn changed_names(old: &'static str, new: &'static str, is_gui: bool) -> Option<Vec<String>> {
+        let old = parse_pdx_internal(old, "old");
+        let new = parse_pdx_internal(new, "new");
+        let old = definitions(&old, is_gui)?;
+        let new = definitions(&new, is_gui)?;
+        changed_items(&old, &new).map(|keys| keys.iter().map(ToString::to_string).collect())
+    }
+
+    #[test]
+    fn test_changed_items() {
+        let old = "a = { x = 1 }\nb = { y = 2 }\nc = 3\n";
+        // Whitespace and comments don't count as changes.
+        let same = "a = {\n\tx = 1 # note\n}\nb = { y   =   2 }\nc = 3\n";
+        assert_eq!(changed_names(old, same, false), Some(vec![]));
+        let changed = "a = { x = 2 }\nb = { y = 2 }\nc = 3\nd = 4\n";
+        assert_eq!(changed_names(old, changed, false), Some(vec!["a".to_owned(), "d".to_owned()]));
+        let prefixed = "REPLACE:a = { x = 1 }\nb = { y = 2 }\nc = 3\n";
+        assert_eq!(changed_names(old, prefixed, false), Some(vec!["REPLACE:a".to_owned()]));
+        // Removing an item needs the full copy.
+        assert_eq!(changed_names(old, "a = { x = 1 }\nb = { y = 2 }\n", false), None);
+        // So does a repeated item, or a loose value.
+        assert_eq!(changed_names(old, "a = 1\na = 2\nb = 1\nc = 1\n", false), None);
+        assert_eq!(changed_names(old, "a = 1\nb = 1\nc = 1\nd\n", false), None);
+    }
+
+    #[test]
+    fn test_changed_gui_items() {
+        let old = "types T {\n\ttype a = widget { size = { 1 1 } }\n\ttype b = text { }\n}\ntemplate t { x = 1 }\n";
+        let new = "types T {\n\ttype a = widget { size = { 2 1 } }\n\ttype b = text { }\n}\ntemplate t { x = 1 }\n";
+        assert_eq!(changed_names(old, new, true), Some(vec!["a".to_owned()]));
+        let based = "types T {\n\ttype a = widget { size = { 1 1 } }\n\ttype b = button { }\n}\ntemplate t { x = 1 }\n";
+        assert_eq!(changed_names(old, based, true), Some(vec!["b".to_owned()]));
+        // A top-level widget can't be overridden on its own.
+        assert_eq!(changed_names(old, "window = { }\n", true), None);
+    }
+
+    #[test]
+    fn test_advice() {
+        let path = Path::new("common/traits/00_traits.txt");
+        assert!(
+            advice(Some(OverrideStyle::LastWins), path).contains("common/traits/zz_00_traits.txt")
+        );
+        assert!(advice(Some(OverrideStyle::LastWins), path).contains("sorts after"));
+        let path = Path::new("gui/shared/types.gui");
+        assert!(advice(Some(OverrideStyle::FirstWins), path).contains("gui/shared/00_types.gui"));
+        assert!(advice(Some(OverrideStyle::FirstWins), path).contains("sorts before"));
+        assert!(advice(Some(OverrideStyle::Prefixed), path).contains("`REPLACE:`"));
+        assert!(!advice(None, path).contains("such as"));
+    }
+
+    #[test]
+    fn test_item_name() {
+        assert_eq!(item_name("REPLACE:foo"), "foo");
+        assert_eq!(item_name("INJECT:foo"), "foo");
+        assert_eq!(item_name("TRY_INJECT:foo"), "foo");
+        assert_eq!(item_name("REPLACE_OR_CREATE:foo"), "foo");
+        assert_eq!(item_name("foo"), "foo");
+    }
+}
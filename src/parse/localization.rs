@@ -683,18 +683,18 @@ impl<'a> ValueParser<'a> {
             // Clean up leftover state at end
             match state {
                 State::InKey(_) => {
-                    self.value.push(LocaValue::Markup);
+                    self.value.push(LocaValue::Markup(Token::new(&text, loc)));
                 }
-                State::InValue(key, value, loc, bracecount) => {
+                State::InValue(key, value, value_loc, bracecount) => {
                     if key.eq_ignore_ascii_case("tooltip") {
-                        self.handle_tooltip(&value, loc);
+                        self.handle_tooltip(&value, value_loc);
                     }
                     if bracecount > 0 {
                         let msg = "mismatched braces in markup";
                         warn(ErrorKey::Markup).msg(msg).loc(self.loc).push();
                         self.value.push(LocaValue::Error);
                     } else {
-                        self.value.push(LocaValue::Markup);
+                        self.value.push(LocaValue::Markup(Token::new(&text, loc)));
                     }
                 }
             }
@@ -9,7 +9,8 @@ pub mod localization;
 pub mod pdxfile;
 
 /// Global state for parser that need it. Can be passed down to the parser.
-#[derive(Clone, Default, Debug)]
+#[derive(Default, Debug)]
 pub struct ParserMemory {
     pub pdxfile: pdxfile::memory::PdxfileMemory,
+    pub usage: pdxfile::memory::ConstantUsage,
 }
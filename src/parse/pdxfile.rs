@@ -13,7 +13,7 @@ use crate::parse::pdxfile::lexer::{LexError, Lexeme, Lexer};
 use crate::parse::pdxfile::memory::CombinedMemory;
 pub use crate::parse::pdxfile::memory::PdxfileMemory;
 use crate::parse::ParserMemory;
-use crate::report::{err, store_source_file, ErrorKey};
+use crate::report::{err, store_source_file, untidy, ErrorKey};
 use crate::token::{leak, Loc, Token};
 
 mod lexer;
@@ -31,7 +31,7 @@ lalrpop_mod! {
 /// Re-parse a macro (which is a scripted effect, trigger, or modifier that uses $ parameters)
 /// after argument substitution. A full re-parse is needed because the game engine allows tricks
 /// such as passing `#` as a macro argument in order to comment out the rest of a line.
-pub fn parse_pdx_macro(inputs: &[Token], global: &PdxfileMemory, local: &PdxfileMemory) -> Block {
+pub fn parse_pdx_macro(inputs: &[Token], global: &ParserMemory, local: &PdxfileMemory) -> Block {
     let mut combined = CombinedMemory::from_local(global, local.clone());
     match parser::FileParser::new().parse(inputs, &mut combined, Lexer::new(inputs)) {
         Ok(block) => block,
@@ -49,9 +49,10 @@ fn parse_pdx(entry: &FileEntry, content: &'static str, memory: &ParserMemory) ->
     loc.line = 1;
     loc.column = 1;
     let inputs = [Token::from_static_str(content, loc)];
-    let mut combined = CombinedMemory::new(&memory.pdxfile);
+    let mut combined = CombinedMemory::new(memory);
     match parser::FileParser::new().parse(&inputs, &mut combined, Lexer::new(&inputs)) {
         Ok(mut block) => {
+            combined.check_unused();
             block.loc = file_loc;
             block
         }
@@ -80,7 +81,7 @@ pub fn parse_reader_export(
     entry: &FileEntry,
     content: String,
     offset: usize,
-    global: &mut PdxfileMemory,
+    global: &mut ParserMemory,
 ) {
     let content = leak(content);
     store_source_file(entry.fullpath().to_path_buf(), &content[offset..]);
@@ -92,7 +93,8 @@ pub fn parse_reader_export(
     let mut combined = CombinedMemory::new(global);
     match parser::FileParser::new().parse(&inputs, &mut combined, Lexer::new(&inputs)) {
         Ok(_) => {
-            global.merge(combined.into_local());
+            let local = combined.into_local();
+            global.pdxfile.merge(local);
         }
         Err(e) => {
             eprintln!("Internal error: parsing file {} failed.\n{e}", entry.path().display());
@@ -177,14 +179,34 @@ fn define_var(memory: &mut CombinedMemory, token: &Token, cmp: Comparator, value
         let msg = format!("expected `{name} =`");
         err(ErrorKey::ReaderDirectives).msg(msg).loc(token).push();
     }
-    if memory.has_variable(name) {
+    if memory.has_local_variable(name) {
         let msg = format!("`{name}` is already defined as a reader variable");
-        err(ErrorKey::ReaderDirectives).msg(msg).loc(token).push();
+        let (other, _) = memory.get_definition(name).unwrap();
+        err(ErrorKey::ReaderDirectives)
+            .msg(msg)
+            .loc(token)
+            .loc_msg(other, "previous definition")
+            .push();
+    } else if let Some((other, other_value)) = memory.get_definition(name) {
+        // Defined globally, in `reader_export`
+        if other_value.is(value.as_str()) {
+            let msg = format!("`{name}` is already defined with the same value");
+            untidy(ErrorKey::ReaderDirectives).msg(msg).loc(token).loc_msg(other, "here").push();
+        } else {
+            let msg = format!("`{name}` shadows a global reader variable with a different value");
+            let info = format!("this file has {value} but the global value is {other_value}");
+            err(ErrorKey::ReaderDirectives)
+                .msg(msg)
+                .info(info)
+                .loc(token)
+                .loc_msg(other, "global definition")
+                .push();
+        }
     } else if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
         let msg = "reader variable names must start with an ascii letter";
         err(ErrorKey::ReaderDirectives).msg(msg).loc(token).push();
     } else {
-        memory.set_variable(name.to_string(), value);
+        memory.set_variable(name.to_string(), token.clone(), value);
     }
 }
 
@@ -214,7 +236,7 @@ fn report_error(error: ParseError<usize, Lexeme, LexError>, mut file_loc: Loc) {
     };
 }
 
-fn get_numeric_var(memory: &CombinedMemory, name: &Token) -> f64 {
+fn get_numeric_var(memory: &mut CombinedMemory, name: &Token) -> f64 {
     if let Some(value) = name.get_number() {
         value
     } else if let Some(v) = memory.get_variable(name.as_str()) {
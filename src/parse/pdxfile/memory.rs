@@ -1,16 +1,48 @@
 //! Maintain the parser state for `@values` and `@:` directives.
 
+use std::sync::Mutex;
+
 use crate::block::Block;
-use crate::helpers::TigerHashMap;
-use crate::token::Token;
+use crate::helpers::{TigerHashMap, TigerHashSet};
+use crate::parse::ParserMemory;
+use crate::report::{untidy, ErrorKey};
+use crate::token::{Loc, Token};
+
+/// How `@values` and macros are used across all files.
+/// Files are parsed in parallel, so this can't be tracked in the shared global memory itself.
+#[derive(Default, Debug)]
+pub struct ConstantUsage {
+    /// The names of global `@values` and macros that have been used by any file.
+    used_globals: Mutex<TigerHashSet<String>>,
+    /// Every `@value` definition seen by the parser, as (name, value) pairs, by where they are.
+    /// Used to answer queries about where a value comes from.
+    definitions: Mutex<TigerHashMap<Loc, (Token, Token)>>,
+}
+
+impl ConstantUsage {
+    fn mark_used_global(&self, key: &str) {
+        let mut used = self.used_globals.lock().unwrap();
+        if !used.contains(key) {
+            used.insert(key.to_owned());
+        }
+    }
+
+    fn add_definition(&self, name: &Token, value: &Token) {
+        // Macro expansions are re-parsed, with a different link for each call site, so leave out
+        // the link to keep from storing their definitions more than once.
+        let loc = Loc { link_idx: None, ..name.loc };
+        self.definitions.lock().unwrap().insert(loc, (name.clone(), value.clone()));
+    }
+}
 
 /// Definitions retained by the parser, to handle @values and macros.
 #[derive(Clone, Default, Debug)]
 pub struct PdxfileMemory {
     /// Pdx calls them variables even though they are constants.
-    variables: TigerHashMap<String, Token>,
-    /// Macros defined with `@:define`.
-    blocks: TigerHashMap<String, Block>,
+    /// Each entry holds the name as it appeared in the definition, and the value.
+    variables: TigerHashMap<String, (Token, Token)>,
+    /// Macros defined with `@:define`, with the name as it appeared in the definition.
+    blocks: TigerHashMap<String, (Token, Block)>,
 }
 
 impl PdxfileMemory {
@@ -18,55 +50,133 @@ impl PdxfileMemory {
         self.variables.extend(other.variables);
         self.blocks.extend(other.blocks);
     }
+
+    /// Report global values and macros that no file has used.
+    /// Must only be called after all files have been parsed.
+    pub fn check_unused(&self, usage: &ConstantUsage) {
+        let used = usage.used_globals.lock().unwrap();
+        for (name, (key, _)) in &self.variables {
+            if !used.contains(name) {
+                let msg = format!("reader variable `{name}` is never used");
+                untidy(ErrorKey::UnusedConstant).msg(msg).loc(key).push();
+            }
+        }
+        for (name, (key, _)) in &self.blocks {
+            if !used.contains(&format!("@:{name}")) {
+                let msg = format!("reader macro `{name}` is never used");
+                untidy(ErrorKey::UnusedConstant).msg(msg).loc(key).push();
+            }
+        }
+    }
+
+    /// Return all definitions of the `@value` called `name`, as (name, value, is global) tuples.
+    /// The global definition, if any, comes first.
+    pub fn query_variable(&self, name: &str, usage: &ConstantUsage) -> Vec<(Token, Token, bool)> {
+        let global = self.variables.get(name);
+        let mut found: Vec<_> = usage
+            .definitions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, (key, _))| key.as_str().trim_start_matches('@') == name)
+            .filter(|(loc, _)| global.map_or(true, |(global, _)| global.loc != **loc))
+            .map(|(loc, (key, value))| (*loc, key.clone(), value.clone()))
+            .collect();
+        found.sort_unstable_by_key(|(loc, _, _)| *loc);
+        let mut found: Vec<_> =
+            found.into_iter().map(|(_, key, value)| (key, value, false)).collect();
+        if let Some((key, value)) = global {
+            found.insert(0, (key.clone(), value.clone(), true));
+        }
+        found
+    }
 }
 
 pub struct CombinedMemory<'global> {
-    global: &'global PdxfileMemory,
+    /// The global memory, and how its values are used across files.
+    memory: &'global ParserMemory,
     local: PdxfileMemory,
+    /// The names of the local values and macros that have been used.
+    used: TigerHashSet<String>,
 }
 
 impl<'global> CombinedMemory<'global> {
-    pub fn new(global: &'global PdxfileMemory) -> Self {
-        Self { global, local: PdxfileMemory::default() }
+    pub fn new(memory: &'global ParserMemory) -> Self {
+        Self::from_local(memory, PdxfileMemory::default())
+    }
+
+    pub fn from_local(memory: &'global ParserMemory, local: PdxfileMemory) -> Self {
+        Self { memory, local, used: TigerHashSet::default() }
+    }
+
+    /// Remember that a named value or macro was used.
+    /// Macro names are marked with an `@:` prefix to keep them apart from value names.
+    fn mark_used(&mut self, key: &str, is_local: bool) {
+        if is_local {
+            if !self.used.contains(key) {
+                self.used.insert(key.to_owned());
+            }
+        } else {
+            self.memory.usage.mark_used_global(key);
+        }
     }
 
-    pub fn from_local(global: &'global PdxfileMemory, local: PdxfileMemory) -> Self {
-        Self { global, local }
+    /// Get a previously set named value, and remember that it was used.
+    pub fn get_variable(&mut self, key: &str) -> Option<&Token> {
+        let is_local = self.local.variables.contains_key(key);
+        if is_local || self.memory.pdxfile.variables.contains_key(key) {
+            self.mark_used(key, is_local);
+        }
+        self.local
+            .variables
+            .get(key)
+            .or_else(|| self.memory.pdxfile.variables.get(key))
+            .map(|(_, value)| value)
     }
 
-    /// Get a previously set named value.
-    pub fn get_variable(&self, key: &str) -> Option<&Token> {
-        self.local.variables.get(key).or_else(|| self.global.variables.get(key))
+    /// Get the definition of a previously set named value, as (name, value).
+    /// Unlike [`Self::get_variable`], this does not count as using the value.
+    pub fn get_definition(&self, key: &str) -> Option<&(Token, Token)> {
+        self.local.variables.get(key).or_else(|| self.memory.pdxfile.variables.get(key))
     }
 
-    /// Check if a variable has been defined previously.
-    pub fn has_variable(&self, key: &str) -> bool {
-        self.local.variables.contains_key(key) || self.global.variables.contains_key(key)
+    /// Check if a variable has been defined previously in this file.
+    pub fn has_local_variable(&self, key: &str) -> bool {
+        self.local.variables.contains_key(key)
     }
 
-    /// Insert a local value definition.
-    pub fn set_variable(&mut self, key: String, value: Token) {
-        self.local.variables.insert(key, value);
+    /// Insert a local value definition. `name` is the name as it appeared in the definition.
+    pub fn set_variable(&mut self, key: String, name: Token, value: Token) {
+        self.memory.usage.add_definition(&name, &value);
+        self.local.variables.insert(key, (name, value));
     }
 
-    /// Retrieve a previously defined macro.
-    pub fn get_block(&self, key: &str) -> Option<&Block> {
-        self.local.blocks.get(key).or_else(|| self.global.blocks.get(key))
+    /// Retrieve a previously defined macro, and remember that it was used.
+    pub fn get_block(&mut self, key: &str) -> Option<&Block> {
+        let is_local = self.local.blocks.contains_key(key);
+        if is_local || self.memory.pdxfile.blocks.contains_key(key) {
+            self.mark_used(&format!("@:{key}"), is_local);
+        }
+        self.local
+            .blocks
+            .get(key)
+            .or_else(|| self.memory.pdxfile.blocks.get(key))
+            .map(|(_, block)| block)
     }
 
     /// Check if a macro has been defined under this name.
     pub fn has_block(&self, key: &str) -> bool {
-        self.local.blocks.contains_key(key) || self.global.blocks.contains_key(key)
+        self.local.blocks.contains_key(key) || self.memory.pdxfile.blocks.contains_key(key)
     }
 
-    /// Define a macro.
-    pub fn define_block(&mut self, key: String, block: Block) {
-        self.local.blocks.insert(key, block);
+    /// Define a macro. `name` is the name as it appeared in the definition.
+    pub fn define_block(&mut self, key: String, name: Token, block: Block) {
+        self.local.blocks.insert(key, (name, block));
     }
 
     /// Return the global part of the memory.
-    pub fn as_global(&self) -> &PdxfileMemory {
-        self.global
+    pub fn as_global(&self) -> &'global ParserMemory {
+        self.memory
     }
 
     /// Clone the local part of the memory.
@@ -74,6 +184,23 @@ impl<'global> CombinedMemory<'global> {
         self.local.clone()
     }
 
+    /// Report the local values and macros that were defined but not used.
+    /// Must only be called once the whole file has been parsed.
+    pub fn check_unused(&self) {
+        for (name, (key, _)) in &self.local.variables {
+            if !self.used.contains(name) {
+                let msg = format!("reader variable `{name}` is never used in this file");
+                untidy(ErrorKey::UnusedConstant).msg(msg).loc(key).push();
+            }
+        }
+        for (name, (key, _)) in &self.local.blocks {
+            if !self.used.contains(&format!("@:{name}")) {
+                let msg = format!("reader macro `{name}` is never used in this file");
+                untidy(ErrorKey::UnusedConstant).msg(msg).loc(key).push();
+            }
+        }
+    }
+
     /// Ignore the global part of the memory.
     #[cfg(feature = "ck3")]
     pub fn into_local(self) -> PdxfileMemory {
@@ -13,7 +13,7 @@ use crate::block::Block;
 use crate::fileset::FileEntry;
 use crate::parse::pdxfile::parse_pdx_file;
 #[cfg(feature = "ck3")]
-use crate::parse::pdxfile::{parse_reader_export, PdxfileMemory};
+use crate::parse::pdxfile::parse_reader_export;
 use crate::parse::ParserMemory;
 use crate::report::{err, warn, ErrorKey};
 
@@ -114,7 +114,7 @@ impl PdxFile {
     }
 
     #[cfg(feature = "ck3")]
-    pub fn reader_export(entry: &FileEntry, memory: &mut PdxfileMemory) {
+    pub fn reader_export(entry: &FileEntry, memory: &mut ParserMemory) {
         if let Some(contents) = Self::read_utf8(entry) {
             if contents.starts_with(BOM_CHAR) {
                 parse_reader_export(entry, contents, BOM_UTF8_LEN, memory);
@@ -0,0 +1,433 @@
+//! Static estimation of how expensive script is to run, to find the `on_action`s, pulses, and
+//! frequently checked triggers that are most likely to cause lag.
+//!
+//! The cost of a block is the estimated number of triggers and effects evaluated when it runs.
+//! Iterators multiply the cost of their contents by the expected size of the list they go over,
+//! scripted triggers and effects are expanded with their macro arguments, and events and
+//! `on_action`s that get triggered are included as well.
+//!
+//! The list sizes and frequencies used here are rough guesses for a late game. The numbers are
+//! only meant for comparing costs with each other, not as measurements.
+
+use std::borrow::Cow;
+
+use crate::block::{Block, BlockItem, Field, BV};
+use crate::everything::Everything;
+use crate::helpers::{ActionOrEvent, TigerHashMap};
+use crate::item::Item;
+use crate::macros::{macro_args, MacroCache};
+use crate::report::{tips, ErrorKey};
+use crate::scopes::iterated_list;
+use crate::token::Token;
+use crate::tooltipped::Tooltipped;
+
+/// Expected number of items in the larger builtin lists.
+const LIST_SIZES: &[(&str, f64)] = &[
+    ("living_character", 20_000.0),
+    ("character", 20_000.0),
+    ("ruler", 3_000.0),
+    ("independent_ruler", 500.0),
+    ("county", 3_000.0),
+    ("barony", 9_000.0),
+    ("province", 9_000.0),
+    ("country", 200.0),
+    ("state", 800.0),
+    ("pop", 10_000.0),
+];
+
+/// Expected number of items in lists that aren't in `LIST_SIZES`, such as a character's children.
+const DEFAULT_LIST_SIZE: f64 = 10.0;
+
+/// Expected number of iterations of a `while` loop that has no `count`.
+const WHILE_ITERATIONS: f64 = 100.0;
+
+/// Expected number of characters that pulses for playable characters are run for, and that the AI
+/// checks decisions for.
+const PLAYABLE_CHARACTERS: f64 = 3_000.0;
+
+/// How many times a gui trigger is checked compared to a yearly pulse.
+const GUI_CHECKS: f64 = 100.0;
+
+/// Only report entry points with a weighted cost of at least this much.
+const REPORT_THRESHOLD: f64 = 100_000.0;
+
+/// Report at most this many entry points, starting with the most expensive.
+const MAX_REPORTS: usize = 10;
+
+/// Don't show more than this many steps of a hot path.
+const MAX_STEPS: usize = 8;
+
+/// One step in the path to the most expensive part of a block.
+#[derive(Clone, Debug)]
+enum Step {
+    /// An event or `on_action` that was triggered.
+    Trigger(ActionOrEvent),
+    /// An iterator over a list of the given expected size, or a loop.
+    Iterate(Token, f64),
+    /// A scripted trigger, scripted effect, or script value.
+    Call(Token),
+}
+
+#[derive(Clone, Debug, Default)]
+struct Cost {
+    /// Estimated number of triggers and effects evaluated.
+    total: f64,
+    /// The way to the most expensive part.
+    path: Vec<Step>,
+}
+
+impl Cost {
+    fn single() -> Self {
+        Cost { total: 1.0, path: Vec::new() }
+    }
+
+    /// Add the cost of a part of the same block. The path follows the most expensive part.
+    fn add(&mut self, other: Cost, hottest: &mut f64) {
+        self.total += other.total;
+        if other.total > *hottest {
+            *hottest = other.total;
+            self.path = other.path;
+        }
+    }
+
+    fn with_step(mut self, step: Step) -> Self {
+        self.path.insert(0, step);
+        self
+    }
+}
+
+/// A place where the game starts running script, with an estimate of how often it does so.
+struct Entry {
+    key: Token,
+    what: String,
+    cost: Cost,
+    /// How many times it runs relative to a yearly pulse.
+    weight: f64,
+    /// Explanation of `weight`.
+    frequency: &'static str,
+}
+
+struct Analyzer<'a> {
+    data: &'a Everything,
+    /// Costs of scripted triggers and effects, per call.
+    calls: MacroCache<Cost>,
+    /// Costs of events, `on_action`s, and script values, by name. An entry is inserted before
+    /// the cost is computed, so that recursion stops there.
+    named: TigerHashMap<String, Cost>,
+}
+
+impl<'a> Analyzer<'a> {
+    fn new(data: &'a Everything) -> Self {
+        Analyzer { data, calls: MacroCache::default(), named: TigerHashMap::default() }
+    }
+
+    fn block_cost(&mut self, block: &Block) -> Cost {
+        let mut cost = Cost::default();
+        let mut hottest = 0.0;
+        for item in block.iter_items() {
+            match item {
+                BlockItem::Field(Field(key, _, bv)) => {
+                    cost.add(self.field_cost(key, bv), &mut hottest);
+                }
+                BlockItem::Value(_) => cost.add(Cost::single(), &mut hottest),
+                BlockItem::Block(block) => cost.add(self.block_cost(block), &mut hottest),
+            }
+        }
+        cost
+    }
+
+    fn bv_cost(&mut self, bv: &BV) -> Cost {
+        match bv {
+            BV::Value(token) => self.value_cost(token),
+            BV::Block(block) => self.block_cost(block),
+        }
+    }
+
+    /// The cost of a value, which is more than trivial if it's the name of a script value.
+    fn value_cost(&mut self, token: &Token) -> Cost {
+        if let Some(bv) = self.data.script_values.get_bv(token.as_str()) {
+            let name = format!("script value {token}");
+            let cost = self.named_cost(name, |analyzer| analyzer.bv_cost(bv));
+            return cost.with_step(Step::Call(token.clone()));
+        }
+        Cost::single()
+    }
+
+    fn field_cost(&mut self, key: &Token, bv: &BV) -> Cost {
+        if let Some((prefix, name)) = key.split_once('_') {
+            if ["every", "any", "random", "ordered"].iter().any(|p| prefix.is(p)) {
+                if let Some(list) = iterated_list(&name, self.data) {
+                    let size = LIST_SIZES
+                        .iter()
+                        .find(|(l, _)| *l == list)
+                        .map_or(DEFAULT_LIST_SIZE, |(_, size)| *size);
+                    return self.loop_cost(key, size, bv);
+                }
+            }
+        }
+
+        if key.is("while") {
+            let count = bv
+                .get_block()
+                .and_then(|block| block.get_field_value("count"))
+                .and_then(Token::get_number)
+                .unwrap_or(WHILE_ITERATIONS);
+            return self.loop_cost(key, count, bv);
+        }
+
+        if key.is("trigger_event") {
+            let target = match bv {
+                BV::Value(token) => Some((token, false)),
+                BV::Block(block) => block
+                    .get_field_value("id")
+                    .map(|token| (token, false))
+                    .or_else(|| block.get_field_value("on_action").map(|token| (token, true))),
+            };
+            return match target {
+                Some((token, false)) => self.event_cost(token),
+                Some((token, true)) => self.on_action_cost(token),
+                None => Cost::single(),
+            };
+        }
+
+        if let Some(trigger) = self.data.get_trigger(key) {
+            let args = macro_args(&trigger.macro_parms(), bv);
+            let body = trigger.body(key, &args, self.data);
+            return self.call_cost(key, &trigger.key, &args, body);
+        }
+        if let Some(effect) = self.data.get_effect(key) {
+            let args = macro_args(&effect.macro_parms(), bv);
+            let body = effect.body(key, &args, self.data);
+            return self.call_cost(key, &effect.key, &args, body);
+        }
+
+        let mut cost = Cost::single();
+        let mut hottest = 0.0;
+        cost.add(self.bv_cost(bv), &mut hottest);
+        cost
+    }
+
+    fn loop_cost(&mut self, key: &Token, count: f64, bv: &BV) -> Cost {
+        let inner = self.bv_cost(bv);
+        let cost = Cost { total: 1.0 + count * inner.total, path: inner.path };
+        cost.with_step(Step::Iterate(key.clone(), count))
+    }
+
+    /// The cost of calling a scripted trigger or effect.
+    /// Calls without arguments are cached by the definition, and calls with arguments by call site.
+    fn call_cost(
+        &mut self,
+        key: &Token,
+        definition: &Token,
+        args: &[(&'static str, Token)],
+        body: Option<Cow<Block>>,
+    ) -> Cost {
+        let cache_key = if args.is_empty() { definition } else { key };
+        let mut cached = None;
+        self.calls.perform(cache_key, args, Tooltipped::No, false, |cost| {
+            cached = Some(cost.clone());
+        });
+        let cost = if let Some(cost) = cached {
+            cost
+        } else if let Some(body) = body {
+            self.calls.insert(cache_key, args, Tooltipped::No, false, Cost::single());
+            let cost = self.block_cost(&body);
+            self.calls.insert(cache_key, args, Tooltipped::No, false, cost.clone());
+            cost
+        } else {
+            Cost::single()
+        };
+        cost.with_step(Step::Call(key.clone()))
+    }
+
+    fn named_cost(&mut self, name: String, f: impl FnOnce(&mut Self) -> Cost) -> Cost {
+        if let Some(cost) = self.named.get(&name) {
+            return cost.clone();
+        }
+        self.named.insert(name.clone(), Cost::single());
+        let cost = f(self);
+        self.named.insert(name, cost.clone());
+        cost
+    }
+
+    fn event_cost(&mut self, token: &Token) -> Cost {
+        let data = self.data;
+        let Some(event) = data.events.get_event(token.as_str()) else {
+            return Cost::single();
+        };
+        let cost =
+            self.named_cost(format!("event {token}"), |analyzer| analyzer.block_cost(&event.block));
+        cost.with_step(Step::Trigger(ActionOrEvent::new_event(token.clone())))
+    }
+
+    fn on_action_cost(&mut self, token: &Token) -> Cost {
+        let data = self.data;
+        let Some(on_action) = data.on_actions.get(token.as_str()) else {
+            return Cost::single();
+        };
+        let cost = self.named_cost(format!("on_action {token}"), |analyzer| {
+            let mut cost = Cost::default();
+            let mut hottest = 0.0;
+            for (_, block) in on_action.iter_definitions() {
+                cost.add(analyzer.on_action_block_cost(block), &mut hottest);
+            }
+            cost
+        });
+        cost.with_step(Step::Trigger(ActionOrEvent::new_action(token.clone())))
+    }
+
+    fn on_action_block_cost(&mut self, block: &Block) -> Cost {
+        let mut cost = Cost::default();
+        let mut hottest = 0.0;
+        for (key, bv) in block.iter_assignments_and_definitions() {
+            let Some(list) = bv.get_block() else {
+                if key.is("fallback") {
+                    if let Some(token) = bv.get_value() {
+                        cost.add(self.on_action_cost(token), &mut hottest);
+                    }
+                }
+                continue;
+            };
+            let fires_events = key.is("events") || key.is("random_events") || key.is("first_valid");
+            let fires_actions = key.is("on_actions")
+                || key.is("random_on_action")
+                || key.is("first_valid_on_action");
+            if !fires_events && !fires_actions {
+                cost.add(self.block_cost(list), &mut hottest);
+                continue;
+            }
+            // Only one of a `random_` list is picked, so count the most expensive one.
+            let pick_one = key.starts_with("random_");
+            let mut costs = Vec::new();
+            for token in list.iter_values() {
+                costs.push(if fires_events {
+                    self.event_cost(token)
+                } else {
+                    self.on_action_cost(token)
+                });
+            }
+            for (_, token) in list.iter_assignments() {
+                if token.is("0") {
+                    continue;
+                }
+                costs.push(if fires_events {
+                    self.event_cost(token)
+                } else {
+                    self.on_action_cost(token)
+                });
+            }
+            if pick_one {
+                if let Some(max) =
+                    costs.into_iter().reduce(|a, b| if b.total > a.total { b } else { a })
+                {
+                    cost.add(max, &mut hottest);
+                }
+            } else {
+                for c in costs {
+                    cost.add(c, &mut hottest);
+                }
+            }
+        }
+        cost
+    }
+}
+
+/// Guess how often a pulse `on_action` runs, relative to once a year, from its name.
+fn pulse_frequency(name: &str) -> Option<(f64, &'static str)> {
+    let (runs, frequency) = if name.contains("quarterly") {
+        (4.0, "every quarter")
+    } else if name.contains("monthly") {
+        (12.0, "every month")
+    } else if name.contains("five_year") {
+        (0.2, "every five years")
+    } else if name.contains("three_year") {
+        (1.0 / 3.0, "every three years")
+    } else if name.contains("yearly") {
+        (1.0, "every year")
+    } else {
+        return None;
+    };
+    if name.contains("playable") {
+        let frequency = match frequency {
+            "every quarter" => "every quarter for every playable character",
+            "every month" => "every month for every playable character",
+            "every five years" => "every five years for every playable character",
+            "every three years" => "every three years for every playable character",
+            _ => "every year for every playable character",
+        };
+        Some((runs * PLAYABLE_CHARACTERS, frequency))
+    } else {
+        Some((runs, frequency))
+    }
+}
+
+fn collect_entries(analyzer: &mut Analyzer, data: &Everything) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for key in data.on_actions.iter_keys() {
+        let Some(on_action) = data.on_actions.get(key.as_str()) else {
+            continue;
+        };
+        let mut cost = analyzer.on_action_cost(key);
+        // The path starts with the on_action itself, which is already the report's location.
+        cost.path.remove(0);
+        let (weight, frequency) = pulse_frequency(key.as_str()).unwrap_or((1.0, "each time"));
+        // Report at the last definition, which is where a mod adds to an existing on_action.
+        let key = on_action.iter_definitions().last().map_or(key, |(key, _)| key).clone();
+        entries.push(Entry { key, what: "on_action".to_owned(), cost, weight, frequency });
+    }
+
+    for (key, block) in data.database.iter_key_block(Item::Decision) {
+        if let Some(is_shown) = block.get_field_block("is_shown") {
+            entries.push(Entry {
+                key: key.clone(),
+                what: "decision `is_shown`".to_owned(),
+                cost: analyzer.block_cost(is_shown),
+                weight: PLAYABLE_CHARACTERS,
+                frequency: "for every playable character",
+            });
+        }
+    }
+
+    for (key, block) in data.database.iter_key_block(Item::ScriptedGui) {
+        for field in ["is_shown", "is_valid"] {
+            if let Some(trigger) = block.get_field_block(field) {
+                entries.push(Entry {
+                    key: key.clone(),
+                    what: format!("scripted gui `{field}`"),
+                    cost: analyzer.block_cost(trigger),
+                    weight: GUI_CHECKS,
+                    frequency: "many times while the gui is shown",
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// Estimate the cost of the `on_action`s, pulses, decisions, and scripted guis, and report the
+/// most expensive ones with the path to their most expensive part.
+pub fn check_performance(data: &Everything) {
+    let mut analyzer = Analyzer::new(data);
+    let mut entries = collect_entries(&mut analyzer, data);
+    entries.retain(|entry| entry.cost.total * entry.weight >= REPORT_THRESHOLD);
+    entries.sort_by(|a, b| (b.cost.total * b.weight).total_cmp(&(a.cost.total * a.weight)));
+
+    for entry in entries.into_iter().take(MAX_REPORTS) {
+        let msg = format!(
+            "{} `{}` is expensive: about {:.0} checks and effects each time, {}",
+            entry.what, entry.key, entry.cost.total, entry.frequency
+        );
+        let info = "the way to the most expensive part is shown below";
+        let mut builder = tips(ErrorKey::Performance).msg(msg).info(info).loc(&entry.key);
+        for step in entry.cost.path.iter().take(MAX_STEPS) {
+            builder = match step {
+                Step::Trigger(trace) => builder.loc_msg(trace.token(), "triggered from here"),
+                Step::Iterate(key, count) => {
+                    builder.loc_msg(key, format!("repeats about {count:.0} times"))
+                }
+                Step::Call(key) => builder.loc_msg(key, "called here"),
+            };
+        }
+        builder.push();
+    }
+}
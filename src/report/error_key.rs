@@ -55,7 +55,14 @@ pub enum ErrorKey {
     Colors,
     UnusedLocalization,
     LocalizationKeyCollision,
+    LocalizationLength,
+    MixedScript,
+    MissingGlyph,
+    Performance,
     UnusedFile,
+    UnusedConstant,
+    VanillaDrift,
+    FileOverride,
     UnknownList,
     Choice,
     UseOfThis,
@@ -24,6 +24,10 @@ use crate::token::{leak, Loc};
 
 static ERRORS: Lazy<Mutex<Errors>> = Lazy::new(|| Mutex::new(Errors::default()));
 
+/// Unit tests that look at the stored reports must hold this lock, because the storage is global.
+#[cfg(test)]
+pub(crate) static TEST_REPORTS_LOCK: Mutex<()> = Mutex::new(());
+
 #[allow(missing_debug_implementations)]
 pub struct Errors {
     pub(crate) output: RefCell<Box<dyn Write + Send>>,
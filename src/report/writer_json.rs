@@ -1,5 +1,6 @@
 use serde_json::json;
 
+use crate::game_version::installed_version;
 use crate::report::errors::Errors;
 use crate::report::writer::kind_tag;
 use crate::report::LogReport;
@@ -31,6 +32,7 @@ pub fn log_report_json(errors: &mut Errors, report: &LogReport) {
         "message": &report.msg,
         "info": &report.info,
         "locations": pointers,
+        "game_version": installed_version(),
     });
 
     if let Err(e) = serde_json::to_writer_pretty(errors.output.get_mut(), &report) {
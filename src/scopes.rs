@@ -7,6 +7,7 @@ use bitflags::bitflags;
 use crate::context::ScopeContext;
 use crate::everything::Everything;
 use crate::game::Game;
+use crate::game_data::GameData;
 use crate::item::Item;
 use crate::report::{err, ErrorKey};
 use crate::token::Token;
@@ -372,7 +373,7 @@ pub fn scope_to_scope(name: &Token, inscopes: Scopes) -> Option<(Scopes, Scopes)
         err(ErrorKey::Removed).strong().msg(msg).info(explanation).loc(name).push();
         return Some((Scopes::all(), Scopes::all_but_none()));
     } else {
-        None
+        GameData::get()?.event_target(&name_lc)
     }
 }
 
@@ -416,6 +417,26 @@ pub fn needs_prefix(arg: &str, data: &Everything, scopes: Scopes) -> Option<&'st
     }
 }
 
+/// Return the name of the builtin list that an iterator with this name goes over, looking
+/// through scripted lists to their base. Returns `None` if it's not an iterator.
+pub fn iterated_list(name: &Token, data: &Everything) -> Option<String> {
+    let scope_iterator = match Game::game() {
+        #[cfg(feature = "ck3")]
+        Game::Ck3 => crate::ck3::scopes::scope_iterator,
+        #[cfg(feature = "vic3")]
+        Game::Vic3 => crate::vic3::scopes::scope_iterator,
+        #[cfg(feature = "imperator")]
+        Game::Imperator => crate::imperator::scopes::scope_iterator,
+    };
+    let name_lc = name.as_str().to_ascii_lowercase();
+    if scope_iterator(&name_lc).is_some() {
+        Some(name_lc)
+    } else {
+        let base = data.scripted_lists.base(name)?.as_str().to_ascii_lowercase();
+        scope_iterator(&base).is_some().then_some(base)
+    }
+}
+
 /// Look up an iterator, which is a script element that executes its block multiple times, once for
 /// each applicable scope value. Iterators may be builtin (the usual case) or may be scripted lists.
 ///
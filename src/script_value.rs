@@ -15,11 +15,13 @@ use crate::scopes::{scope_iterator, Scopes};
 use crate::token::Token;
 use crate::tooltipped::Tooltipped;
 use crate::trigger::{validate_target_ok_this, validate_trigger, validate_trigger_key_bv};
+use crate::trigger_logic::{check_ifelse_reachable, check_switch_cases};
 use crate::validate::{
     precheck_iterator_fields, validate_ifelse_sequence, validate_inside_iterator,
     validate_iterator_fields, validate_scope_chain, ListType,
 };
 use crate::validator::Validator;
+use crate::value_range::{eval_script_value, eval_script_value_token};
 
 /// Validate a block that's part of a script value.
 /// * `have_value`: indicates whether this script value has had some sort of value set already.
@@ -52,6 +54,7 @@ fn validate_inner(
     let mut saved_value = false;
 
     validate_ifelse_sequence(block, "if", "else_if", "else");
+    check_ifelse_reachable(block, "if", "else_if", data);
     vd.set_allow_questionmark_equals(true);
     vd.unknown_fields_cmp(|token, cmp, bv| {
         if token.is("save_temporary_scope_as") {
@@ -90,6 +93,10 @@ fn validate_inner(
                 warn(ErrorKey::Logic).msg(msg).loc(token).push();
             }
             validate_bv(bv, data, sc, check_desc);
+            if (token.is("divide") || token.is("modulo")) && eval_script_value(bv, data).is_zero() {
+                let msg = format!("`{token}` by a value that is always 0");
+                err(ErrorKey::Range).msg(msg).loc(token).push();
+            }
             made_changes = true;
             saved_value = false;
         } else if token.is("round") || token.is("ceiling") || token.is("floor") || token.is("abs") {
@@ -147,6 +154,7 @@ fn validate_inner(
                 let mut vd = Validator::new(block, data);
                 vd.req_field("trigger");
                 if let Some(target) = vd.field_value("trigger").cloned() {
+                    check_switch_cases(block);
                     vd.set_allow_questionmark_equals(true);
                     vd.unknown_block_fields(|key, block| {
                         if !key.is("fallback") {
@@ -265,6 +273,13 @@ fn validate_minmax_range(
     vd.multi_field_validated("max", |bv, data| {
         validate_bv(bv, data, sc, check_desc);
     });
+    if let (Some(min), Some(max)) = (block.get_field("min"), block.get_field("max")) {
+        let (min_range, max_range) = (eval_script_value(min, data), eval_script_value(max, data));
+        if min_range.min > max_range.max {
+            let msg = format!("`min` is always more than `max` ({min_range} > {max_range})");
+            warn(ErrorKey::Range).msg(msg).loc(min).push();
+        }
+    }
 }
 
 /// Validate `if` or `else_if` blocks that are part of a script value.
@@ -335,9 +350,16 @@ pub(crate) fn validate_bv(bv: &BV, data: &Everything, sc: &mut ScopeContext, che
                 // It's a range like { 1 5 }
                 let vec = vd.values();
                 if vec.len() == 2 {
-                    for v in vec {
+                    for v in &vec {
                         validate_target_ok_this(v, data, sc, Scopes::Value | Scopes::Bool);
                     }
+                    let low = eval_script_value_token(vec[0], data);
+                    let high = eval_script_value_token(vec[1], data);
+                    if low.min > high.max {
+                        let msg = format!("range is reversed ({low} > {high})");
+                        let info = "the lower bound should come first";
+                        warn(ErrorKey::Range).msg(msg).info(info).loc(b).push();
+                    }
                 } else {
                     warn(ErrorKey::Validation).msg("invalid script value range").loc(b).push();
                 }
@@ -0,0 +1,165 @@
+//! Loader for the FMOD sound event names that are available to the game and the mod.
+
+use std::fs::read;
+use std::path::PathBuf;
+
+use crate::fileset::{FileEntry, FileHandler, FileKind};
+use crate::helpers::TigerHashSet;
+use crate::lowercase::Lowercase;
+use crate::parse::ParserMemory;
+use crate::report::{err, untidy, ErrorKey};
+use crate::token::Loc;
+
+/// The prefix of sound event paths, as opposed to buses, snapshots, etc.
+const EVENT_PREFIX: &str = "event:/";
+
+/// The sound events listed in `GUIDs.txt` files and FMOD strings banks.
+#[derive(Clone, Debug, Default)]
+pub struct SoundEvents {
+    /// Event paths, stored in lowercase because lookup is case-insensitive.
+    events: TigerHashSet<Lowercase<'static>>,
+    /// Whether the base game's `GUIDs.txt` was found. If it wasn't, the built-in list of sounds
+    /// has to be used instead.
+    vanilla_guids: bool,
+}
+
+impl SoundEvents {
+    pub fn exists(&self, name: &str) -> bool {
+        self.events.contains(&Lowercase::new(name))
+    }
+
+    /// Whether the events from the base game are known, so that the built-in list of sounds is
+    /// not needed.
+    pub fn has_vanilla_events(&self) -> bool {
+        self.vanilla_guids
+    }
+
+    /// Parse a `GUIDs.txt` file as exported by FMOD Studio.
+    /// Each line is a GUID in braces followed by a path, such as `event:/SFX/UI/click`.
+    fn load_guids(entry: &FileEntry, content: &str) -> Vec<String> {
+        let mut events = Vec::new();
+        for (nr, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match line.split_once(char::is_whitespace) {
+                Some((guid, path)) if guid.starts_with('{') && guid.ends_with('}') => {
+                    let path = path.trim();
+                    if path.starts_with(EVENT_PREFIX) {
+                        events.push(path.to_lowercase());
+                    }
+                }
+                _ => {
+                    let mut loc = Loc::from(entry);
+                    loc.line = u32::try_from(nr + 1).unwrap_or(0);
+                    let msg = "expected a {guid} followed by a path";
+                    untidy(ErrorKey::ParseError).msg(msg).loc(loc).push();
+                }
+            }
+        }
+        events
+    }
+
+    /// Find event paths in an FMOD strings bank.
+    /// The bank format is not documented, so this just looks for the path strings in the data.
+    fn load_strings_bank(bytes: &[u8]) -> Vec<String> {
+        let mut events = Vec::new();
+        let prefix = EVENT_PREFIX.as_bytes();
+        let mut rest = bytes;
+        while let Some(pos) = rest.windows(prefix.len()).position(|w| w == prefix) {
+            rest = &rest[pos..];
+            let len = rest.iter().position(|&b| !(b' '..=b'~').contains(&b)).unwrap_or(rest.len());
+            if len > prefix.len() {
+                events.push(String::from_utf8_lossy(&rest[..len]).to_lowercase());
+            }
+            rest = &rest[len.max(prefix.len())..];
+        }
+        events
+    }
+
+    /// Load the base game's sound files that were replaced by the mod, so that the events they
+    /// define are still known. The game reads all `GUIDs.txt` files and banks, not only the last
+    /// one.
+    pub fn load_replaced_file(&mut self, entry: &FileEntry, parser: &ParserMemory) {
+        if entry.kind() >= FileKind::LoadedMod(0) {
+            return;
+        }
+        if let Some(events) = self.load_file(entry, parser) {
+            self.handle_file(entry, events);
+        }
+    }
+}
+
+impl FileHandler<Vec<String>> for SoundEvents {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("sound")
+    }
+
+    fn load_file(&self, entry: &FileEntry, _parser: &ParserMemory) -> Option<Vec<String>> {
+        let filename = entry.filename().to_string_lossy();
+        let is_guids = filename.eq_ignore_ascii_case("GUIDs.txt");
+        if !is_guids && !filename.to_lowercase().ends_with(".strings.bank") {
+            return None;
+        }
+
+        match read(entry.fullpath()) {
+            Ok(bytes) if is_guids => {
+                Some(Self::load_guids(entry, &String::from_utf8_lossy(&bytes)))
+            }
+            Ok(bytes) => Some(Self::load_strings_bank(&bytes)),
+            Err(e) => {
+                let msg = "could not read file";
+                let info = format!("{e:#}");
+                err(ErrorKey::ReadError).msg(msg).info(info).loc(entry).push();
+                None
+            }
+        }
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, events: Vec<String>) {
+        if entry.kind() < FileKind::LoadedMod(0)
+            && !events.is_empty()
+            && entry.filename().eq_ignore_ascii_case("GUIDs.txt")
+        {
+            self.vanilla_guids = true;
+        }
+        self.events.extend(events.into_iter().map(Lowercase::from_string_unchecked));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{take_reports, TEST_REPORTS_LOCK};
+
+    #[test]
+    fn test_load_guids() {
+        let entry = FileEntry::new(
+            PathBuf::from("sound/GUIDs.txt"),
+            FileKind::Mod,
+            PathBuf::from("sound/GUIDs.txt"),
+        );
+        let content = "{0a1b2c3d-0000-0000-0000-000000000001} event:/SFX/UI/Click\n\
+                       \n\
+                       {0a1b2c3d-0000-0000-0000-000000000002} bus:/Master\n\
+                       not a guid event:/SFX/UI/Hover\n\
+                       {0a1b2c3d-0000-0000-0000-000000000003}   event:/Music/Theme  \n";
+        let _guard = TEST_REPORTS_LOCK.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        take_reports();
+        let events = SoundEvents::load_guids(&entry, content);
+        assert_eq!(events, vec!["event:/sfx/ui/click", "event:/music/theme"]);
+        let reports = take_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].msg, "expected a {guid} followed by a path");
+        assert_eq!(reports[0].pointers[0].loc.line, 4);
+    }
+
+    #[test]
+    fn test_load_strings_bank() {
+        let bytes =
+            b"RIFF\x10\0\0\0event:/SFX/UI/Click\0\x01event:/\0bus:/Master\0event:/Music/Theme";
+        let events = SoundEvents::load_strings_bank(bytes);
+        assert_eq!(events, vec!["event:/sfx/ui/click", "event:/music/theme"]);
+    }
+}
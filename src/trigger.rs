@@ -12,9 +12,12 @@ use crate::date::Date;
 use crate::desc::validate_desc;
 use crate::everything::Everything;
 use crate::game::Game;
+use crate::game_data::GameData;
+use crate::game_version::{tables_confidence, Table};
 use crate::helpers::stringify_choices;
 use crate::item::Item;
 use crate::lowercase::Lowercase;
+use crate::macros::{macro_args, validate_macro_args};
 #[cfg(feature = "vic3")]
 use crate::modif::{verify_modif_exists, ModifKinds};
 use crate::report::{err, fatal, tips, warn, ErrorKey, Severity};
@@ -24,6 +27,7 @@ use crate::scopes::{
 use crate::script_value::validate_script_value;
 use crate::token::{Loc, Token};
 use crate::tooltipped::Tooltipped;
+use crate::trigger_logic::{check_conjunction, check_ifelse_reachable, check_switch_cases};
 use crate::validate::{
     precheck_iterator_fields, validate_ifelse_sequence, validate_inside_iterator,
     validate_iterator_fields, ListType,
@@ -46,9 +50,24 @@ pub fn scope_trigger(name: &Token, data: &Everything) -> Option<(Scopes, Trigger
         #[cfg(feature = "imperator")]
         Game::Imperator => crate::imperator::tables::triggers::scope_trigger,
     };
-    scope_trigger(name, data)
+    scope_trigger(name, data).or_else(|| GameData::get()?.trigger(name))
 }
 
+/// The callers of [`validate_trigger_internal`] whose blocks are true only if all the triggers
+/// in them are true.
+const CONJUNCTIONS: &[&str] = &[
+    "",
+    "and",
+    "nand",
+    "any_false",
+    "trigger_if",
+    "trigger_else_if",
+    "trigger_else",
+    "custom_description",
+    "custom_tooltip",
+    "modifier",
+];
+
 /// The standard interface to trigger validation. Validates a trigger in the given [`ScopeContext`].
 ///
 /// `tooltipped` determines what warnings are emitted related to tooltippability of the triggers
@@ -224,6 +243,10 @@ pub fn validate_trigger_internal(
     }
 
     validate_ifelse_sequence(block, "trigger_if", "trigger_else_if", "trigger_else");
+    check_ifelse_reachable(block, "trigger_if", "trigger_else_if", data);
+    if in_list || CONJUNCTIONS.iter().any(|c| caller == c) {
+        check_conjunction(block, data);
+    }
 
     vd.unknown_fields_any_cmp(|key, cmp, bv| {
         if key.is("value") {
@@ -318,12 +341,14 @@ pub fn validate_trigger_key_bv(
 
     // Scripted trigger?
     if let Some(trigger) = data.get_trigger(key) {
+        let parms = trigger.macro_parms();
+        trigger.record_call(key, bv, &macro_args(&parms, bv), sc);
         match bv {
             BV::Value(token) => {
                 if !(token.is("yes") || token.is("no") || token.is("YES") || token.is("NO")) {
                     warn(ErrorKey::Validation).msg("expected yes or no").loc(token).push();
                 }
-                if !trigger.macro_parms().is_empty() {
+                if !parms.is_empty() {
                     fatal(ErrorKey::Macro).msg("expected macro arguments").loc(token).push();
                     return side_effects;
                 }
@@ -332,30 +357,12 @@ pub fn validate_trigger_key_bv(
                 trigger.validate_call(key, data, sc, tooltipped, negated);
             }
             BV::Block(block) => {
-                let parms = trigger.macro_parms();
                 if parms.is_empty() {
                     let msg = "this scripted trigger does not need macro arguments";
                     fatal(ErrorKey::Macro).msg(msg).loc(block).push();
-                } else {
-                    let mut vec = Vec::new();
-                    let mut vd = Validator::new(block, data);
-                    vd.set_max_severity(max_sev);
-                    for parm in &parms {
-                        if let Some(token) = vd.field_value(parm) {
-                            vec.push(token.clone());
-                        } else {
-                            let msg = format!("this scripted trigger needs parameter {parm}");
-                            err(ErrorKey::Macro).msg(msg).loc(block).push();
-                            return side_effects;
-                        }
-                    }
-                    vd.unknown_value_fields(|key, _value| {
-                        let msg = format!("this scripted trigger does not need parameter {key}");
-                        let info = "supplying an unneeded parameter often causes a crash";
-                        fatal(ErrorKey::Macro).msg(msg).info(info).loc(key).push();
-                    });
-
-                    let args: Vec<_> = parms.into_iter().zip(vec).collect();
+                } else if let Some(args) =
+                    validate_macro_args("scripted trigger", &parms, block, data, max_sev)
+                {
                     // TODO: check side_effects
                     trigger.validate_macro_expansion(key, &args, data, sc, tooltipped, negated);
                 }
@@ -471,7 +478,8 @@ pub fn validate_trigger_key_bv(
                 } else {
                     // TODO: warn if trying to use iterator here
                     let msg = format!("unknown token `{part}`");
-                    err(ErrorKey::UnknownField).msg(msg).loc(part).push();
+                    let conf = tables_confidence(&[Table::Triggers, Table::EventTargets]);
+                    err(ErrorKey::UnknownField).conf(conf).msg(msg).loc(part).push();
                     sc.close();
                     return side_effects;
                 }
@@ -918,6 +926,7 @@ fn match_trigger_bv(
                     vd.req_field("trigger");
                     if let Some(target) = vd.field_value("trigger") {
                         let target = target.clone();
+                        check_switch_cases(block);
                         let mut count = 0;
                         vd.set_allow_questionmark_equals(true);
                         vd.unknown_block_fields(|key, block| {
@@ -970,6 +979,9 @@ fn match_trigger_bv(
             bv.expect_value();
             side_effects = true; // have to assume it's possible
         }
+        Trigger::Unchecked => {
+            side_effects = true; // have to assume it's possible
+        }
     }
 
     if matches!(cmp, Comparator::Equals(_)) {
@@ -1087,7 +1099,13 @@ pub fn validate_target_ok_this(
 
                     // TODO: warn if trying to use iterator here
                     let msg = format!("unknown token `{part}`");
-                    err(ErrorKey::UnknownField).msg(msg).opt_info(opt_info).loc(part).push();
+                    let conf = tables_confidence(&[Table::Triggers, Table::EventTargets]);
+                    err(ErrorKey::UnknownField)
+                        .conf(conf)
+                        .msg(msg)
+                        .opt_info(opt_info)
+                        .loc(part)
+                        .push();
                     sc.close();
                     return Scopes::all();
                 }
@@ -1415,7 +1433,8 @@ pub fn validate_argument(
         validate_argument_scope(part_flags, entry, func, arg, data, sc);
     } else {
         let msg = format!("unknown token `{func}`");
-        err(ErrorKey::Validation).msg(msg).loc(func).push();
+        let conf = tables_confidence(&[Table::Triggers]);
+        err(ErrorKey::Validation).conf(conf).msg(msg).loc(func).push();
     }
 }
 
@@ -1487,6 +1506,8 @@ pub enum Trigger {
     Special,
 
     UncheckedValue,
+    /// trigger is only known from the game data loaded with `--game-data`
+    Unchecked,
 }
 
 /// This function checks if the trigger is one that can be used at the end of a scope chain on the
@@ -0,0 +1,535 @@
+//! Logic-level checks of trigger blocks, looking at how the triggers in a block relate to each
+//! other rather than at each trigger by itself.
+//!
+//! Only simple triggers are understood: `key = value` lines, `NOT` or `NOR` around them, and
+//! comparisons against a number. Everything else is treated as unknown, so the checks here only
+//! report problems that are certain.
+
+use std::cmp::Ordering;
+
+use crate::block::{Block, BlockItem, Comparator, Eq::*, Field, BV};
+use crate::everything::Everything;
+use crate::helpers::TigerHashMap;
+use crate::report::{untidy, warn, ErrorKey};
+use crate::token::Token;
+use crate::trigger::{scope_trigger, Trigger};
+use crate::value_range::eval_script_value_token;
+
+/// Boolean triggers that are each other's opposites.
+/// The second one of each pair is rewritten to the first one, negated.
+const OPPOSITES: &[(&str, &str)] = &[("is_female", "is_male")];
+
+/// A trigger of the form `key = value`, or its negation.
+#[derive(Debug)]
+struct Literal<'a> {
+    key: &'a Token,
+    value: &'a Token,
+    /// The key and value in a form that can be compared, with opposites resolved.
+    canonical: (String, String),
+    positive: bool,
+    /// Whether the trigger was written inside a `NOT` or `NOR`.
+    in_not: bool,
+}
+
+impl Literal<'_> {
+    fn describe(&self) -> String {
+        if self.in_not {
+            format!("`NOT = {{ {} = {} }}`", self.key, self.value)
+        } else {
+            format!("`{} = {}`", self.key, self.value)
+        }
+    }
+}
+
+/// One end of a range that a compared value has to be in.
+#[derive(Debug, Clone, Copy)]
+struct Limit {
+    value: f64,
+    strict: bool,
+}
+
+impl Limit {
+    /// Return whether this lower limit is at least as tight as `other`.
+    fn tighter_low(self, other: Limit) -> bool {
+        match self.value.total_cmp(&other.value) {
+            Ordering::Greater => true,
+            Ordering::Equal => self.strict || !other.strict,
+            Ordering::Less => false,
+        }
+    }
+
+    /// Return whether this upper limit is at least as tight as `other`.
+    fn tighter_high(self, other: Limit) -> bool {
+        match self.value.total_cmp(&other.value) {
+            Ordering::Less => true,
+            Ordering::Equal => self.strict || !other.strict,
+            Ordering::Greater => false,
+        }
+    }
+
+    /// Return whether no value can be both above this lower limit and below the upper limit `high`.
+    fn excludes(self, high: Limit) -> bool {
+        match self.value.total_cmp(&high.value) {
+            Ordering::Greater => true,
+            Ordering::Equal => self.strict || high.strict,
+            Ordering::Less => false,
+        }
+    }
+}
+
+/// A comparison like `age > 50`.
+#[derive(Debug)]
+struct Bound<'a> {
+    key: &'a Token,
+    cmp: Comparator,
+    value: &'a Token,
+    low: Option<Limit>,
+    high: Option<Limit>,
+}
+
+impl Bound<'_> {
+    fn describe(&self) -> String {
+        format!("`{} {} {}`", self.key, self.cmp, self.value)
+    }
+
+    /// Return whether any value that satisfies `self` also satisfies `other`.
+    fn implies(&self, other: &Bound) -> bool {
+        let low_ok = match (self.low, other.low) {
+            (_, None) => true,
+            (Some(ours), Some(theirs)) => ours.tighter_low(theirs),
+            (None, Some(_)) => false,
+        };
+        let high_ok = match (self.high, other.high) {
+            (_, None) => true,
+            (Some(ours), Some(theirs)) => ours.tighter_high(theirs),
+            (None, Some(_)) => false,
+        };
+        low_ok && high_ok
+    }
+}
+
+/// The triggers in a block where all of them have to be true.
+#[derive(Debug, Default)]
+struct Conjunction<'a> {
+    literals: Vec<Literal<'a>>,
+    /// Comparisons, grouped by the lowercased key they compare.
+    bounds: TigerHashMap<String, Vec<Bound<'a>>>,
+    /// Whether every item in the block was understood.
+    complete: bool,
+}
+
+impl<'a> Conjunction<'a> {
+    fn new(block: &'a Block, data: &Everything) -> Self {
+        let mut conj = Conjunction { complete: true, ..Default::default() };
+        for item in block.iter_items() {
+            let BlockItem::Field(Field(key, cmp, bv)) = item else {
+                conj.complete = false;
+                continue;
+            };
+            match bv {
+                BV::Value(value) => {
+                    if !conj.add_bound(key, *cmp, value, data)
+                        && !conj.add_literal(key, *cmp, value, false, data)
+                    {
+                        conj.complete = false;
+                    }
+                }
+                BV::Block(block) => {
+                    let is_not = key.lowercase_is("not") && block.iter_items().count() == 1;
+                    if !is_not && !key.lowercase_is("nor") {
+                        conj.complete = false;
+                        continue;
+                    }
+                    for item in block.iter_items() {
+                        let understood = match item {
+                            BlockItem::Field(Field(key, cmp, BV::Value(value))) => {
+                                conj.add_literal(key, *cmp, value, true, data)
+                            }
+                            _ => false,
+                        };
+                        if !understood {
+                            conj.complete = false;
+                        }
+                    }
+                }
+            }
+        }
+        conj
+    }
+
+    /// Add a trigger like `has_trait = brave` or `is_female = yes`.
+    /// Returns false if it's not a trigger that's understood.
+    fn add_literal(
+        &mut self,
+        key: &'a Token,
+        cmp: Comparator,
+        value: &'a Token,
+        in_not: bool,
+        data: &Everything,
+    ) -> bool {
+        if cmp != Comparator::Equals(Single) || !is_trigger(key, data) {
+            return false;
+        }
+        let mut positive = !in_not;
+        let mut canonical_key = key.as_str().to_ascii_lowercase();
+        let mut canonical_value = value.as_str().to_ascii_lowercase();
+        if canonical_value == "no" {
+            "yes".clone_into(&mut canonical_value);
+            positive = !positive;
+        }
+        if canonical_value == "yes" {
+            let (prefix, name) = match canonical_key.rsplit_once('.') {
+                Some((prefix, name)) => (format!("{prefix}."), name),
+                None => (String::new(), canonical_key.as_str()),
+            };
+            if let Some((opposite, _)) = OPPOSITES.iter().find(|(_, n)| *n == name) {
+                canonical_key = format!("{prefix}{opposite}");
+                positive = !positive;
+            }
+        }
+        let canonical = (canonical_key, canonical_value);
+        self.literals.push(Literal { key, value, canonical, positive, in_not });
+        true
+    }
+
+    /// Add a comparison against a number, like `age > 50`.
+    /// Returns false if it's not a comparison that's understood.
+    fn add_bound(
+        &mut self,
+        key: &'a Token,
+        cmp: Comparator,
+        value: &'a Token,
+        data: &Everything,
+    ) -> bool {
+        // `50 < age` is also possible but rare
+        if key.is_number() {
+            return false;
+        }
+        let Some(number) = eval_script_value_token(value, data).as_exact() else {
+            return false;
+        };
+        let limit = |strict| Some(Limit { value: number, strict });
+        // `age = 50` is not included, because `=` means `>=` for some triggers.
+        let (low, high) = match cmp {
+            Comparator::LessThan => (None, limit(true)),
+            Comparator::AtMost => (None, limit(false)),
+            Comparator::GreaterThan => (limit(true), None),
+            Comparator::AtLeast => (limit(false), None),
+            Comparator::Equals(Double) => (limit(false), limit(false)),
+            _ => return false,
+        };
+        let bound = Bound { key, cmp, value, low, high };
+        self.bounds.entry(key.as_str().to_ascii_lowercase()).or_default().push(bound);
+        true
+    }
+
+    /// Warn about triggers that contradict each other or that are implied by other triggers.
+    fn check(&self) {
+        for (i, lit) in self.literals.iter().enumerate() {
+            for earlier in &self.literals[..i] {
+                if earlier.canonical != lit.canonical {
+                    continue;
+                }
+                if earlier.positive == lit.positive {
+                    let msg = format!("{} is redundant", lit.describe());
+                    untidy(ErrorKey::Logic)
+                        .msg(msg)
+                        .loc(lit.key)
+                        .loc_msg(earlier.key, "already checked here")
+                        .push();
+                } else {
+                    let msg = format!(
+                        "{} and {} can never both be true",
+                        earlier.describe(),
+                        lit.describe()
+                    );
+                    warn(ErrorKey::Logic).msg(msg).loc(lit.key).loc_msg(earlier.key, "here").push();
+                }
+                break;
+            }
+        }
+
+        for bounds in self.bounds.values() {
+            let mut low: Option<(Limit, &Bound)> = None;
+            let mut high: Option<(Limit, &Bound)> = None;
+            let mut redundant = vec![false; bounds.len()];
+            for (i, bound) in bounds.iter().enumerate() {
+                for (j, earlier) in bounds[..i].iter().enumerate() {
+                    if redundant[j] {
+                        continue;
+                    }
+                    let (weaker, stronger, idx) = if earlier.implies(bound) {
+                        (bound, earlier, i)
+                    } else if bound.implies(earlier) {
+                        (earlier, bound, j)
+                    } else {
+                        continue;
+                    };
+                    redundant[idx] = true;
+                    let msg = format!("{} is redundant", weaker.describe());
+                    let msg2 = format!("because of {}", stronger.describe());
+                    untidy(ErrorKey::Logic)
+                        .msg(msg)
+                        .loc(weaker.key)
+                        .loc_msg(stronger.key, msg2)
+                        .push();
+                    break;
+                }
+                if let Some(limit) = bound.low {
+                    if low.map_or(true, |(l, _)| limit.tighter_low(l)) {
+                        low = Some((limit, bound));
+                    }
+                }
+                if let Some(limit) = bound.high {
+                    if high.map_or(true, |(h, _)| limit.tighter_high(h)) {
+                        high = Some((limit, bound));
+                    }
+                }
+            }
+            if let (Some((l, low_bound)), Some((h, high_bound))) = (low, high) {
+                if l.excludes(h) {
+                    let msg = format!(
+                        "{} and {} can never both be true",
+                        low_bound.describe(),
+                        high_bound.describe()
+                    );
+                    warn(ErrorKey::Logic)
+                        .msg(msg)
+                        .loc(high_bound.key)
+                        .loc_msg(low_bound.key, "here")
+                        .push();
+                }
+            }
+        }
+    }
+
+    /// Return whether this conjunction being true means that `other` is also true.
+    /// Only returns true if that is certain.
+    fn implies(&self, other: &Conjunction) -> bool {
+        if !other.complete {
+            return false;
+        }
+        let literals_ok = other.literals.iter().all(|theirs| {
+            self.literals
+                .iter()
+                .any(|ours| ours.canonical == theirs.canonical && ours.positive == theirs.positive)
+        });
+        let bounds_ok = other.bounds.iter().all(|(key, theirs)| {
+            let Some(ours) = self.bounds.get(key) else {
+                return false;
+            };
+            theirs.iter().all(|bound| ours.iter().any(|b| b.implies(bound)))
+        });
+        literals_ok && bounds_ok
+    }
+}
+
+/// Return whether `key` is a trigger, possibly after a chain of scopes, such as `root.is_female`.
+fn is_trigger(key: &Token, data: &Everything) -> bool {
+    if data.get_trigger(key).is_some() {
+        return true;
+    }
+    let Some(name) = key.split('.').pop() else {
+        return false;
+    };
+    if name.as_str().contains(':') {
+        return false;
+    }
+    matches!(scope_trigger(&name, data), Some((_, trigger)) if !matches!(trigger, Trigger::Control))
+}
+
+/// Check a block of triggers that all have to be true, such as a `limit` or `AND` block, for
+/// triggers that contradict each other or are redundant.
+pub fn check_conjunction(block: &Block, data: &Everything) {
+    Conjunction::new(block, data).check();
+}
+
+/// Check an `if` / `else_if` sequence for `else_if` branches that can never be taken, because
+/// their `limit` implies the `limit` of an earlier branch in the same sequence.
+pub fn check_ifelse_reachable(block: &Block, key_if: &str, key_elseif: &str, data: &Everything) {
+    let mut earlier: Vec<(&Token, Conjunction)> = Vec::new();
+    for (key, block) in block.iter_definitions() {
+        if !key.is(key_elseif) {
+            earlier.clear();
+        }
+        if !key.is(key_if) && !key.is(key_elseif) {
+            continue;
+        }
+        let Some(limit) = block.get_field_block("limit") else {
+            earlier.clear();
+            continue;
+        };
+        let conj = Conjunction::new(limit, data);
+        if key.is(key_elseif) {
+            if let Some((earlier_key, _)) = earlier.iter().find(|(_, c)| conj.implies(c)) {
+                let msg = format!("this `{key_elseif}` can never be taken");
+                let info = "its `limit` can only be true if an earlier branch was already taken";
+                warn(ErrorKey::Logic)
+                    .msg(msg)
+                    .info(info)
+                    .loc(key)
+                    .loc_msg(*earlier_key, "earlier branch")
+                    .push();
+            }
+        }
+        earlier.push((key, conj));
+    }
+}
+
+/// Check a `switch` block for cases that are the same as an earlier case.
+/// Only the first matching case is taken, so the later ones can never be used.
+pub fn check_switch_cases(block: &Block) {
+    let mut seen: Vec<&Token> = Vec::new();
+    for (key, _) in block.iter_definitions() {
+        if let Some(earlier) = seen.iter().find(|earlier| earlier.as_str() == key.as_str()) {
+            let msg = format!("duplicate switch case `{key}`");
+            let info = "only the first matching case is used";
+            warn(ErrorKey::DuplicateField)
+                .msg(msg)
+                .info(info)
+                .loc(key)
+                .loc_msg(*earlier, "first case")
+                .push();
+        } else {
+            seen.push(key);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ck3"))]
+mod tests {
+    use super::*;
+
+    use std::path::{Path, PathBuf};
+
+    use crate::parse::pdxfile::parse_pdx_internal;
+    use crate::report::{take_reports, TEST_REPORTS_LOCK};
+
+    const TEST_FILE: &str = "trigger_logic_test.txt";
+
+    fn everything() -> Everything {
+        let vanilla_dir = PathBuf::from("tests/files/ck3");
+        Everything::new(None, Some(&vanilla_dir), Path::new("tests/files/mod2"), Vec::new())
+            .unwrap()
+    }
+
+    /// Run `check` on the parsed `text` and return the messages it reported.
+    fn reports(text: &'static str, check: impl FnOnce(&Block, &Everything)) -> Vec<String> {
+        let _guard = TEST_REPORTS_LOCK.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let data = everything();
+        let block = parse_pdx_internal(text, TEST_FILE);
+        take_reports();
+        check(&block, &data);
+        take_reports()
+            .into_iter()
+            .filter(|report| report.pointers[0].loc.pathname() == Path::new(TEST_FILE))
+            .map(|report| report.msg)
+            .collect()
+    }
+
+    fn conjunction_reports(text: &'static str) -> Vec<String> {
+        reports(text, check_conjunction)
+    }
+
+    fn implies(ours: &'static str, theirs: &'static str) -> bool {
+        let data = everything();
+        let ours = parse_pdx_internal(ours, TEST_FILE);
+        let theirs = parse_pdx_internal(theirs, TEST_FILE);
+        Conjunction::new(&ours, &data).implies(&Conjunction::new(&theirs, &data))
+    }
+
+    #[test]
+    fn test_limit_excludes() {
+        let limit = |value, strict| Limit { value, strict };
+        assert!(limit(6.0, false).excludes(limit(5.0, false)));
+        assert!(!limit(4.0, false).excludes(limit(5.0, false)));
+        assert!(!limit(5.0, false).excludes(limit(5.0, false)));
+        assert!(limit(5.0, true).excludes(limit(5.0, false)));
+        assert!(limit(5.0, false).excludes(limit(5.0, true)));
+    }
+
+    #[test]
+    fn test_conjunction_implies() {
+        assert!(implies("is_female = yes age > 50", "age >= 50"));
+        assert!(implies("age == 20 has_trait = brave", "age < 40"));
+        assert!(!implies("age > 50", "age > 60"));
+        assert!(!implies("age >= 50", "age > 50"));
+        assert!(implies("is_female = yes", "is_male = no"));
+        assert!(implies("NOT = { is_male = yes }", "is_female = yes"));
+        assert!(!implies("is_female = yes", "is_male = yes"));
+        assert!(!implies("is_female = yes", "root.is_female = yes"));
+        // Only certain if every trigger in the other block is understood.
+        assert!(!implies("is_female = yes", "is_female = yes OR = { age > 50 age < 20 }"));
+        assert!(implies("is_female = yes OR = { age > 50 age < 20 }", "is_female = yes"));
+    }
+
+    #[test]
+    fn test_opposites() {
+        let reports = conjunction_reports("is_female = yes is_male = yes");
+        assert_eq!(reports, ["`is_female = yes` and `is_male = yes` can never both be true"]);
+        let reports = conjunction_reports("is_female = yes NOT = { is_male = yes }");
+        assert_eq!(reports, ["`NOT = { is_male = yes }` is redundant"]);
+        let reports = conjunction_reports("is_female = yes is_female = no");
+        assert_eq!(reports, ["`is_female = yes` and `is_female = no` can never both be true"]);
+        // Different scopes
+        assert!(conjunction_reports("is_female = yes root.is_male = yes").is_empty());
+        let reports = conjunction_reports("is_female = yes is_male = no");
+        assert_eq!(reports, ["`is_male = no` is redundant"]);
+    }
+
+    #[test]
+    fn test_not_nor() {
+        let reports = conjunction_reports("NOT = { has_trait = brave } has_trait = brave");
+        assert_eq!(
+            reports,
+            ["`NOT = { has_trait = brave }` and `has_trait = brave` can never both be true"]
+        );
+        let reports = conjunction_reports(
+            "NOR = { has_trait = brave has_trait = craven } has_trait = craven",
+        );
+        assert_eq!(
+            reports,
+            ["`NOT = { has_trait = craven }` and `has_trait = craven` can never both be true"]
+        );
+        // A NOT with more than one trigger in it is true if any of them is false.
+        let text = "NOT = { has_trait = brave has_trait = craven } has_trait = brave";
+        assert!(conjunction_reports(text).is_empty());
+        assert!(conjunction_reports("NOT = { has_trait = brave } has_trait = craven").is_empty());
+        assert!(conjunction_reports("NOT = { OR = { has_trait = brave } } has_trait = brave")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let reports = conjunction_reports("age > 50 age < 40");
+        assert_eq!(reports, ["`age > 50` and `age < 40` can never both be true"]);
+        let reports = conjunction_reports("age > 50 age >= 40");
+        assert_eq!(reports, ["`age >= 40` is redundant"]);
+        assert!(conjunction_reports("age >= 50 age <= 50").is_empty());
+        assert!(conjunction_reports("age > 50 age < 60").is_empty());
+        // `=` can mean `>=`, so it is not compared.
+        assert!(conjunction_reports("age = 50 age > 60").is_empty());
+    }
+
+    #[test]
+    fn test_ifelse_reachable() {
+        let check = |block: &Block, data: &Everything| {
+            check_ifelse_reachable(block, "if", "else_if", data);
+        };
+        let text = "if = { limit = { age > 50 } } else_if = { limit = { age > 60 } }";
+        assert_eq!(reports(text, check), ["this `else_if` can never be taken"]);
+        let text = "if = { limit = { age > 60 } } else_if = { limit = { age > 50 } }";
+        assert!(reports(text, check).is_empty());
+        let text = "if = { limit = { age > 50 } } if = { limit = { age > 60 } }";
+        assert!(reports(text, check).is_empty());
+    }
+
+    #[test]
+    fn test_switch_cases() {
+        let text = "trigger = has_trait brave = { } craven = { } brave = { }";
+        let check = |block: &Block, _: &Everything| check_switch_cases(block);
+        assert_eq!(reports(text, check), ["duplicate switch case `brave`"]);
+        let text = "trigger = has_trait brave = { } craven = { } fallback = { }";
+        assert!(reports(text, check).is_empty());
+    }
+}
@@ -12,6 +12,7 @@ use crate::context::ScopeContext;
 use crate::data::scripted_modifiers::ScriptedModifier;
 use crate::everything::Everything;
 use crate::game::Game;
+use crate::game_version::{tables_confidence, Table};
 use crate::item::Item;
 use crate::lowercase::Lowercase;
 use crate::report::{err, fatal, report, warn, Confidence, ErrorKey, Severity};
@@ -712,7 +713,8 @@ pub fn validate_scope_chain(
                     sc.replace(outscope, part.clone());
                 } else {
                     let msg = format!("unknown token `{part}`");
-                    err(ErrorKey::UnknownField).msg(msg).loc(part).push();
+                    let conf = tables_confidence(&[Table::Effects, Table::EventTargets]);
+                    err(ErrorKey::UnknownField).conf(conf).msg(msg).loc(part).push();
                     return false;
                 }
             }
@@ -0,0 +1,376 @@
+//! Conservative estimates of the values that a script value can take.
+//!
+//! Script values are evaluated abstractly: instead of a number, each step produces a range
+//! `[min, max]` that is guaranteed to contain the real result. Literal numbers, named script values
+//! and inline `@[ ]` math (which the parser already folds to a number) give exact values, while
+//! anything that depends on the game state, such as triggers and scopes, gives an unbounded range.
+
+use std::fmt::{Display, Formatter};
+
+use crate::block::{Block, BlockItem, Field, BV};
+use crate::everything::Everything;
+use crate::game::Game;
+use crate::token::Token;
+
+/// How deep named script values may refer to each other before we give up.
+const MAX_DEPTH: usize = 32;
+
+/// A range of numbers that contains all the values a script value can have.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ValueRange {
+    pub const UNKNOWN: ValueRange = ValueRange { min: f64::NEG_INFINITY, max: f64::INFINITY };
+
+    pub fn exact(value: f64) -> Self {
+        ValueRange { min: value, max: value }
+    }
+
+    /// Return the exact value, if the range has only one.
+    #[allow(clippy::float_cmp)] // the bounds are only equal if they were computed the same way
+    pub fn as_exact(self) -> Option<f64> {
+        (self.min == self.max).then_some(self.min)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.as_exact() == Some(0.0)
+    }
+
+    /// Return a range that covers both `self` and `other`.
+    pub fn union(self, other: ValueRange) -> Self {
+        ValueRange { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    /// Build the smallest range that contains all of `values`.
+    /// If the calculation produced a NaN (such as from `inf * 0`) the range is unknown.
+    fn from_values(values: &[f64]) -> Self {
+        if values.iter().any(|v| v.is_nan()) {
+            return Self::UNKNOWN;
+        }
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        ValueRange { min, max }
+    }
+
+    pub fn add(self, other: ValueRange) -> Self {
+        Self::from_values(&[self.min + other.min, self.max + other.max])
+    }
+
+    pub fn subtract(self, other: ValueRange) -> Self {
+        Self::from_values(&[self.min - other.max, self.max - other.min])
+    }
+
+    pub fn multiply(self, other: ValueRange) -> Self {
+        Self::from_values(&[
+            self.min * other.min,
+            self.min * other.max,
+            self.max * other.min,
+            self.max * other.max,
+        ])
+    }
+
+    pub fn divide(self, other: ValueRange) -> Self {
+        if other.min <= 0.0 && other.max >= 0.0 {
+            return Self::UNKNOWN;
+        }
+        Self::from_values(&[
+            self.min / other.min,
+            self.min / other.max,
+            self.max / other.min,
+            self.max / other.max,
+        ])
+    }
+
+    /// The `min = x` operation, which raises the value to at least `x`.
+    pub fn at_least(self, other: ValueRange) -> Self {
+        ValueRange { min: self.min.max(other.min), max: self.max.max(other.max) }
+    }
+
+    /// The `max = x` operation, which lowers the value to at most `x`.
+    pub fn at_most(self, other: ValueRange) -> Self {
+        ValueRange { min: self.min.min(other.min), max: self.max.min(other.max) }
+    }
+
+    pub fn modulo(self, other: ValueRange) -> Self {
+        match (self.as_exact(), other.as_exact()) {
+            (Some(a), Some(b)) if b != 0.0 => Self::exact(a % b),
+            _ => Self::UNKNOWN,
+        }
+    }
+
+    /// Apply a rounding function. These are all monotonic, so they can be applied to the bounds.
+    fn map_bounds(self, f: impl Fn(f64) -> f64) -> Self {
+        Self::from_values(&[f(self.min), f(self.max)])
+    }
+
+    pub fn round_to(self, other: ValueRange) -> Self {
+        match other.as_exact() {
+            Some(step) if step != 0.0 => self.map_bounds(|v| (v / step).round() * step),
+            _ => Self::UNKNOWN,
+        }
+    }
+
+    pub fn abs(self) -> Self {
+        let max = self.min.abs().max(self.max.abs());
+        if self.min <= 0.0 && self.max >= 0.0 {
+            ValueRange { min: 0.0, max }
+        } else {
+            ValueRange { min: self.min.abs().min(self.max.abs()), max }
+        }
+    }
+}
+
+impl Display for ValueRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = self.as_exact() {
+            write!(f, "{value}")
+        } else {
+            write!(f, "[{}, {}]", self.min, self.max)
+        }
+    }
+}
+
+/// Evaluate a script value, which can be a literal, the name of another script value, a
+/// `{ min max }` range, or a calculation block.
+pub fn eval_script_value(bv: &BV, data: &Everything) -> ValueRange {
+    Evaluator { data, stack: Vec::new() }.eval_bv(bv)
+}
+
+/// Evaluate a script value that's given as a single token, such as one end of a range.
+pub fn eval_script_value_token(token: &Token, data: &Everything) -> ValueRange {
+    Evaluator { data, stack: Vec::new() }.eval_token(token)
+}
+
+/// Evaluate the modifiers of a weight or chance block, such as a `random_list` entry, starting
+/// from `base`. Each modifier only applies if its triggers match, so they can only widen the range.
+pub fn eval_weight_modifiers(base: ValueRange, block: &Block, data: &Everything) -> ValueRange {
+    Evaluator { data, stack: Vec::new() }.eval_modifiers(base, block)
+}
+
+struct Evaluator<'a> {
+    data: &'a Everything,
+    /// The named script values currently being evaluated, to guard against loops.
+    stack: Vec<&'a str>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn eval_bv(&mut self, bv: &'a BV) -> ValueRange {
+        match bv {
+            BV::Value(token) => self.eval_token(token),
+            BV::Block(block) => {
+                if matches!(
+                    block.iter_items().next(),
+                    Some(BlockItem::Block(_) | BlockItem::Value(_))
+                ) {
+                    let mut values = block.iter_values();
+                    match (values.next(), values.next(), values.next()) {
+                        (Some(a), Some(b), None) => self.eval_token(a).union(self.eval_token(b)),
+                        _ => ValueRange::UNKNOWN,
+                    }
+                } else {
+                    self.eval_block(ValueRange::exact(0.0), block)
+                }
+            }
+        }
+    }
+
+    fn eval_token(&mut self, token: &'a Token) -> ValueRange {
+        if let Some(value) = token.get_number() {
+            ValueRange::exact(value)
+        } else if token.is("yes") {
+            ValueRange::exact(1.0)
+        } else if token.is("no") {
+            ValueRange::exact(0.0)
+        } else if let Some(bv) = self.data.script_values.get_bv(token.as_str()) {
+            if self.stack.len() >= MAX_DEPTH || self.stack.contains(&token.as_str()) {
+                return ValueRange::UNKNOWN;
+            }
+            self.stack.push(token.as_str());
+            let result = self.eval_bv(bv);
+            self.stack.pop();
+            result
+        } else {
+            ValueRange::UNKNOWN
+        }
+    }
+
+    /// Evaluate a calculation block, starting from the value `current`.
+    fn eval_block(&mut self, mut current: ValueRange, block: &'a Block) -> ValueRange {
+        // The value before the current if/else_if/else chain, and the results of its branches.
+        let mut chain: Option<(ValueRange, Vec<ValueRange>)> = None;
+        for item in block.iter_items() {
+            let Some(Field(key, _, bv)) = item.get_field() else {
+                continue;
+            };
+            if !key.is("else_if") && !key.is("else") {
+                chain = None;
+            }
+            if key.is("value") {
+                current = self.eval_bv(bv);
+            } else if key.is("add") {
+                current = current.add(self.eval_bv(bv));
+            } else if key.is("subtract") {
+                current = current.subtract(self.eval_bv(bv));
+            } else if key.is("multiply") {
+                current = current.multiply(self.eval_bv(bv));
+            } else if key.is("divide") {
+                current = current.divide(self.eval_bv(bv));
+            } else if key.is("modulo") {
+                current = current.modulo(self.eval_bv(bv));
+            } else if key.is("min") {
+                current = current.at_least(self.eval_bv(bv));
+            } else if key.is("max") {
+                current = current.at_most(self.eval_bv(bv));
+            } else if key.is("round_to") {
+                current = current.round_to(self.eval_bv(bv));
+            } else if key.is("round") || key.is("ceiling") || key.is("floor") || key.is("abs") {
+                let Some(value) = bv.get_value() else {
+                    continue;
+                };
+                if value.is("no") {
+                    continue;
+                }
+                current = if key.is("abs") {
+                    current.abs()
+                } else if key.is("ceiling") || value.is("ceiling") {
+                    current.map_bounds(f64::ceil)
+                } else if key.is("floor") || value.is("floor") {
+                    current.map_bounds(f64::floor)
+                } else {
+                    current.map_bounds(f64::round)
+                };
+            } else if key.is("fixed_range") || key.is("integer_range") {
+                current = match bv.get_block() {
+                    Some(block) => self.eval_minmax_range(block),
+                    None => ValueRange::UNKNOWN,
+                };
+            } else if key.is("if") || key.is("else_if") || key.is("else") {
+                let Some(block) = bv.get_block() else {
+                    continue;
+                };
+                let (start, branches) = chain.get_or_insert_with(|| (current, Vec::new()));
+                branches.push(self.eval_block(*start, block));
+                let taken = branches.iter().copied().reduce(ValueRange::union).unwrap();
+                // Without an `else`, it's possible that none of the branches is taken.
+                current = if key.is("else") { taken } else { taken.union(*start) };
+            } else if key.is("switch") {
+                let Some(block) = bv.get_block() else {
+                    continue;
+                };
+                let start = current;
+                for (_, case) in block.iter_definitions() {
+                    current = current.union(self.eval_block(start, case));
+                }
+            } else if key.is("limit")
+                || key.is("desc")
+                || key.is("format")
+                || key.is("save_temporary_scope_as")
+                || key.is("save_temporary_value_as")
+            {
+                // These don't change the value
+            } else if let Some(block) = bv.get_block() {
+                if is_iterator(key) {
+                    // Iterators can apply their block any number of times.
+                    current = ValueRange::UNKNOWN;
+                } else {
+                    // A block inside a scope, which might not exist.
+                    current = current.union(self.eval_block(current, block));
+                }
+            } else {
+                current = ValueRange::UNKNOWN;
+            }
+        }
+        current
+    }
+
+    fn eval_minmax_range(&mut self, block: &'a Block) -> ValueRange {
+        match (block.get_field("min"), block.get_field("max")) {
+            (Some(min), Some(max)) => self.eval_bv(min).union(self.eval_bv(max)),
+            _ => ValueRange::UNKNOWN,
+        }
+    }
+
+    fn eval_modifiers(&mut self, mut current: ValueRange, block: &'a Block) -> ValueRange {
+        for (key, bv) in block.iter_assignments_and_definitions() {
+            if key.is("modifier") {
+                let Some(block) = bv.get_block().filter(|_| !Game::is_vic3()) else {
+                    // vic3 modifiers are script values whose effect on the weight isn't known.
+                    current = ValueRange::UNKNOWN;
+                    continue;
+                };
+                let mut changed = current;
+                for (key, bv) in block.iter_assignments_and_definitions() {
+                    if key.is("add") {
+                        changed = changed.add(self.eval_bv(bv));
+                    } else if key.is("factor") || key.is("multiply") {
+                        changed = changed.multiply(self.eval_bv(bv));
+                    }
+                }
+                current = current.union(changed);
+            } else if key.is("first_valid") {
+                if let Some(block) = bv.get_block() {
+                    current = current.union(self.eval_modifiers(current, block));
+                }
+            } else if key.is("min") {
+                current = current.at_least(self.eval_bv(bv));
+            } else if key.is("max") {
+                current = current.at_most(self.eval_bv(bv));
+            } else if key.as_str().ends_with("_modifier") {
+                // compare_modifier, opinion_modifier, and the like depend on the game state.
+                current = ValueRange::UNKNOWN;
+            }
+        }
+        current
+    }
+}
+
+fn is_iterator(key: &Token) -> bool {
+    ["every_", "ordered_", "random_", "any_"].iter().any(|prefix| key.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = ValueRange { min: -2.0, max: 3.0 };
+        let b = ValueRange { min: 4.0, max: 5.0 };
+        assert_eq!(a.add(b), ValueRange { min: 2.0, max: 8.0 });
+        assert_eq!(a.subtract(b), ValueRange { min: -7.0, max: -1.0 });
+        assert_eq!(a.multiply(b), ValueRange { min: -10.0, max: 15.0 });
+        assert_eq!(b.divide(a), ValueRange::UNKNOWN);
+        assert_eq!(a.divide(ValueRange::exact(-1.0)), ValueRange { min: -3.0, max: 2.0 });
+        assert_eq!(a.at_least(ValueRange::exact(0.0)), ValueRange { min: 0.0, max: 3.0 });
+        assert_eq!(a.at_most(ValueRange::exact(0.0)), ValueRange { min: -2.0, max: 0.0 });
+        assert_eq!(a.abs(), ValueRange { min: 0.0, max: 3.0 });
+        assert_eq!(ValueRange::UNKNOWN.multiply(ValueRange::exact(0.0)), ValueRange::UNKNOWN);
+    }
+
+    #[test]
+    fn test_clamping() {
+        let a = ValueRange { min: -2.0, max: 3.0 };
+        let b = ValueRange { min: 1.0, max: 5.0 };
+        assert_eq!(a.at_least(b), ValueRange { min: 1.0, max: 5.0 });
+        assert_eq!(a.at_most(b), ValueRange { min: -2.0, max: 3.0 });
+        let c = ValueRange { min: -5.0, max: 0.0 };
+        assert_eq!(a.at_least(c), ValueRange { min: -2.0, max: 3.0 });
+        assert_eq!(a.at_most(c), ValueRange { min: -5.0, max: 0.0 });
+
+        // `value = 0 min = <unknown>` can be anything at least 0, not just 0.
+        let zero = ValueRange::exact(0.0);
+        assert_eq!(zero.at_least(ValueRange::UNKNOWN), ValueRange { min: 0.0, max: f64::INFINITY });
+        assert_eq!(
+            zero.at_most(ValueRange::UNKNOWN),
+            ValueRange { min: f64::NEG_INFINITY, max: 0.0 }
+        );
+        assert_eq!(ValueRange::UNKNOWN.at_least(zero), ValueRange { min: 0.0, max: f64::INFINITY });
+        assert_eq!(
+            ValueRange::UNKNOWN.at_most(zero),
+            ValueRange { min: f64::NEG_INFINITY, max: 0.0 }
+        );
+    }
+}
@@ -2,6 +2,7 @@ use crate::block::Block;
 use crate::db::{Db, DbKind};
 use crate::everything::Everything;
 use crate::game::GameFlags;
+use crate::game_data::GameData;
 use crate::item::{Item, ItemLoader};
 use crate::report::{untidy, ErrorKey};
 use crate::token::Token;
@@ -55,6 +56,7 @@ inventory::submit! {
 
 // LAST UPDATED VIC3 VERSION 1.8.1
 // Taken from gfx/map/map_modes/map_modes.txt
+// The game data from `--game-data` replaces this list if it includes `map_painting_modes`.
 const MAP_PAINTING_MODES: &[&str] = &[
     "clout_nationally",
     "colonizable_provinces",
@@ -128,9 +130,12 @@ impl DbKind for MapMode {
 
         data.verify_exists(Item::Localization, key);
 
-        vd.field_choice("map_painting_mode", MAP_PAINTING_MODES);
-        vd.field_choice("map_painting_mode_secondary", MAP_PAINTING_MODES);
-        vd.field_choice("map_painting_mode_alternate", MAP_PAINTING_MODES);
+        let painting_modes = GameData::get()
+            .and_then(|game_data| game_data.list("map_painting_modes"))
+            .unwrap_or_else(|| MAP_PAINTING_MODES.to_vec());
+        vd.field_choice("map_painting_mode", &painting_modes);
+        vd.field_choice("map_painting_mode_secondary", &painting_modes);
+        vd.field_choice("map_painting_mode_alternate", &painting_modes);
         vd.field_choice("map_names", MAP_NAMES);
         vd.field_list("map_markers"); // TODO widget names from gui/map_markers.gui
         vd.field_list("map_mode_lists"); // unknown list items. panel name?
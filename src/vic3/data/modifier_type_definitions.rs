@@ -3,7 +3,7 @@ use crate::db::{Db, DbKind};
 use crate::everything::Everything;
 use crate::game::GameFlags;
 use crate::item::{Item, ItemLoader};
-use crate::modif::{verify_modif_exists, ModifKinds};
+use crate::modif::{validate_modif_format, verify_modif_exists, ModifKinds};
 use crate::report::Severity;
 use crate::token::Token;
 use crate::validator::Validator;
@@ -31,6 +31,7 @@ impl DbKind for ModifierTypeDefinition {
         let (loca, loca_desc) = modif_loc(key, data);
         data.verify_exists_implied(Item::Localization, &loca, key);
         data.verify_exists_implied(Item::Localization, &loca_desc, key);
+        validate_modif_format(key, block, &loca, data);
 
         verify_modif_exists(key, data, ModifKinds::all(), Severity::Untidy);
 
@@ -1,3 +1,5 @@
+use crate::game_version::TableVersions;
+
 pub mod datafunctions;
 pub mod effects;
 pub mod localization;
@@ -7,3 +9,12 @@ pub mod on_action;
 pub mod rules;
 pub mod sounds;
 pub mod triggers;
+
+/// The game versions that the tables were last updated for.
+/// Keep these in sync with the `LAST UPDATED` markers when updating the tables.
+pub const TABLE_VERSIONS: TableVersions = TableVersions {
+    triggers: "1.8.1",
+    effects: "1.8.4",
+    event_targets: "1.7.6",
+    modifiers: "1.8.4",
+};
@@ -106,11 +106,24 @@ fn test_mod1() {
     report.expect("event required field option");
     let report = take_report_contains(&mut reports, events, "duplicate event");
     assert!(report.is_none());
+    let report = take_report(
+        &mut reports,
+        events,
+        "this file is the same as the base game file it replaces",
+    );
+    report.expect("identical copy of vanilla file");
 
     let events = "events/test-script-values.txt";
     let report = take_report_contains(&mut reports, events, "`else` with a `limit`");
     report.expect("scriptvalue else with a limit");
 
+    let events = "events/test-random-chance.txt";
+    let report = take_report(&mut reports, events, "chance is never more than 0 (0)");
+    let report = report.expect("random block that never runs");
+    assert!(report.pointers[0].loc.line == 17);
+    let report = take_report_contains(&mut reports, events, "chance is never more than 0");
+    assert!(report.is_none());
+
     dbg!(&reports);
     assert!(reports.is_empty());
 }
@@ -151,3 +164,47 @@ fn test_mod2() {
     dbg!(&reports);
     assert!(reports.is_empty());
 }
+
+#[test]
+fn test_mod3() {
+    let mut reports = check_mod_helper("mod3");
+
+    let characters = "history/characters/test-history.txt";
+    let report = take_report(
+        &mut reports,
+        characters,
+        "father 1001 died on 1019.5.1, before the birth of 1003",
+    );
+    let report = report.expect("father died long before birth");
+    assert!(report.pointers[0].loc.line == 17);
+    let report = take_report_contains(&mut reports, characters, "before the birth of 1005");
+    assert!(report.is_none());
+    let report = take_report(
+        &mut reports,
+        characters,
+        "mother 1002 was only 13 years old at the birth of 1003",
+    );
+    let report = report.expect("parent age on the date of birth");
+    assert!(report.pointers[0].loc.line == 18);
+    let report = take_report(
+        &mut reports,
+        characters,
+        "1006 was only 15 years old when marrying on 1025.1.1",
+    );
+    report.expect("marriage age");
+
+    let titles = "history/titles/test-history.txt";
+    let report =
+        take_report(&mut reports, titles, "1004 died on 1020.1.1 but holds c_test until 1030.1.1");
+    let report = report.expect("holder dies while holding");
+    assert!(report.pointers[0].loc.line == 3);
+    let report = take_report(
+        &mut reports,
+        titles,
+        "theocratic holder 1006 of d_test has faith catholic on 1030.1.1, but liege 1007 has faith orthodox",
+    );
+    report.expect("theocracy faith differs from liege");
+
+    dbg!(&reports);
+    assert!(reports.is_empty());
+}
@@ -0,0 +1,23 @@
+//! The `eval` subcommand, for finding out which values a script value can have.
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::tiger::ModArgs;
+use crate::GameConsts;
+
+#[derive(Args)]
+pub(crate) struct EvalArgs {
+    #[clap(flatten)]
+    mod_args: ModArgs,
+    /// The name of a script value.
+    script_value: String,
+}
+
+pub(crate) fn run_eval(game_consts: &GameConsts, mut args: EvalArgs) -> Result<()> {
+    let script_value = args.script_value;
+    args.mod_args.print_query(game_consts, |everything| {
+        let range = everything.eval_script_value(&script_value)?;
+        Ok(format!("{script_value}: {range}\n"))
+    })
+}
@@ -0,0 +1,29 @@
+//! The `expand` subcommand, for seeing the script that a call to a scripted trigger or effect runs.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::Args;
+
+use crate::tiger::ModArgs;
+use crate::GameConsts;
+
+#[derive(Args)]
+pub(crate) struct ExpandArgs {
+    #[clap(flatten)]
+    mod_args: ModArgs,
+    /// The call site, as `path/to/file.txt:line`. The path is relative to the mod directory.
+    call_site: String,
+}
+
+pub(crate) fn run_expand(game_consts: &GameConsts, mut args: ExpandArgs) -> Result<()> {
+    let Some((path, line)) = args.call_site.rsplit_once(':') else {
+        bail!("expected the call site as file:line");
+    };
+    let Ok(line) = line.parse::<u32>() else {
+        bail!("expected a line number after the `:` in {}", args.call_site);
+    };
+    let path = PathBuf::from(path);
+
+    args.mod_args.print_query(game_consts, |everything| everything.expand_call_site(&path, line))
+}
@@ -0,0 +1,30 @@
+//! The `gui` subcommands, for inspecting the mod's `.gui` files.
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::tiger::ModArgs;
+use crate::GameConsts;
+
+#[derive(Subcommand)]
+pub(crate) enum GuiCommands {
+    /// Print the fully resolved widget tree of a window, with types expanded and templates and
+    /// blockoverrides applied. Each property is annotated with the file and line it came from.
+    Dump(DumpArgs),
+}
+
+#[derive(Args)]
+pub(crate) struct DumpArgs {
+    #[clap(flatten)]
+    mod_args: ModArgs,
+    /// The `name` of a top-level widget, or the name of a widget type.
+    window: String,
+}
+
+pub(crate) fn run_gui(game_consts: &GameConsts, command: GuiCommands) -> Result<()> {
+    match command {
+        GuiCommands::Dump(mut args) => {
+            args.mod_args.print_query(game_consts, |everything| everything.dump_gui(&args.window))
+        }
+    }
+}
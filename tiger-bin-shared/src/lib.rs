@@ -1,5 +1,10 @@
 mod auto;
+mod eval;
+mod expand;
 mod gamedir;
+mod gui;
+mod loca;
+mod query;
 mod tiger;
 mod update;
 
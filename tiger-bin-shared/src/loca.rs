@@ -0,0 +1,91 @@
+//! The `loca` subcommands, for working with the mod's localization files.
+
+use std::mem::forget;
+
+use anyhow::{bail, Result};
+use clap::{Args, Subcommand};
+
+use crate::tiger::ModArgs;
+use crate::GameConsts;
+
+#[derive(Subcommand)]
+pub(crate) enum LocaCommands {
+    /// Copy localization entries that are missing in some languages from a reference language.
+    /// The entries are appended to correctly named per-language files, which are created if needed.
+    Sync(SyncArgs),
+    /// Show what a localization key looks like after its macros are expanded.
+    /// Data functions are annotated with the datatype they return, and markup and icons are shown
+    /// as plain text.
+    Render(RenderArgs),
+}
+
+#[derive(Args)]
+pub(crate) struct SyncArgs {
+    #[clap(flatten)]
+    mod_args: ModArgs,
+    /// The language to copy the entries from.
+    #[clap(long, default_value = "english")]
+    from: String,
+    /// Which language to add missing entries to. Can be given more than once.
+    /// Defaults to all the languages the mod already has localization for.
+    #[clap(long)]
+    lang: Vec<String>,
+    /// Comment to add after each copied entry, to mark it as needing translation.
+    /// Use an empty string for no comment.
+    #[clap(long, default_value = "untranslated")]
+    marker: String,
+}
+
+#[derive(Args)]
+pub(crate) struct RenderArgs {
+    #[clap(flatten)]
+    mod_args: ModArgs,
+    /// The localization key to render.
+    key: String,
+    /// The language to render it in.
+    #[clap(long, default_value = "english")]
+    lang: String,
+    /// A named scope that the localization is used with, and its type, such as `actor=character`.
+    /// Use `root=type` to set the root scope. Can be given more than once.
+    #[clap(long)]
+    scope: Vec<String>,
+}
+
+pub(crate) fn run_loca(game_consts: &GameConsts, command: LocaCommands) -> Result<()> {
+    match command {
+        LocaCommands::Sync(mut args) => {
+            let everything = args.mod_args.load(game_consts)?;
+
+            let langs: Vec<&str> = if args.lang.is_empty() {
+                everything.mod_languages().to_vec()
+            } else {
+                args.lang.iter().map(String::as_str).collect()
+            };
+            let written = everything.sync_localization(&args.from, &langs, &args.marker)?;
+            if written.is_empty() {
+                eprintln!("No missing localization entries found.");
+            }
+            for (path, count) in written {
+                eprintln!("Added {count} entries to {}", path.display());
+            }
+
+            // Properly dropping `everything` takes a noticeable amount of time, and we're exiting anyway.
+            forget(everything);
+            Ok(())
+        }
+        LocaCommands::Render(mut args) => {
+            let mut scopes = Vec::new();
+            for scope in &args.scope {
+                let Some((name, stype)) = scope.split_once('=') else {
+                    bail!("expected --scope name=type, got {scope}");
+                };
+                scopes.push((name.to_owned(), stype.to_owned()));
+            }
+
+            args.mod_args.print_query(game_consts, |everything| {
+                let rendered = everything.render_localization(&args.key, &args.lang, &scopes)?;
+                Ok(format!("{rendered}\n"))
+            })
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! The `query` subcommands, for looking up how things in the mod are defined.
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::tiger::ModArgs;
+use crate::GameConsts;
+
+#[derive(Subcommand)]
+pub(crate) enum QueryCommands {
+    /// Print the value of a reader variable such as `@foo`, and every place where it is defined.
+    Constant(ConstantArgs),
+}
+
+#[derive(Args)]
+pub(crate) struct ConstantArgs {
+    #[clap(flatten)]
+    mod_args: ModArgs,
+    /// The name of the reader variable, with or without the leading `@`.
+    name: String,
+}
+
+pub(crate) fn run_query(game_consts: &GameConsts, command: QueryCommands) -> Result<()> {
+    match command {
+        QueryCommands::Constant(mut args) => args
+            .mod_args
+            .print_query(game_consts, |everything| everything.query_constant(&args.name)),
+    }
+}
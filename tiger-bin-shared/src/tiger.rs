@@ -1,4 +1,4 @@
-use std::{mem::forget, path::PathBuf};
+use std::{fs::write, mem::forget, path::PathBuf};
 
 use anyhow::{bail, Result};
 use clap::{Args, Parser, Subcommand};
@@ -7,11 +7,17 @@ use tiger_lib::ModFile;
 #[cfg(feature = "vic3")]
 use tiger_lib::ModMetadata;
 use tiger_lib::{
-    disable_ansi_colors, emit_reports, set_show_loaded_mods, set_show_vanilla, suppress_from_json,
-    validate_config_file, Everything,
+    detect_game_version, disable_ansi_colors, emit_reports, outdated_tables, set_installed_version,
+    set_show_loaded_mods, set_show_vanilla, suppress_from_json, take_reports, validate_config_file,
+    Everything, GameData,
 };
 
+use crate::eval::{run_eval, EvalArgs};
+use crate::expand::{run_expand, ExpandArgs};
 use crate::gamedir::find_game_directory_steam;
+use crate::gui::{run_gui, GuiCommands};
+use crate::loca::{run_loca, LocaCommands};
+use crate::query::{run_query, QueryCommands};
 use crate::update::update;
 use crate::GameConsts;
 
@@ -23,8 +29,13 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
+    // The mod arguments are flattened here rather than inside `ValidateArgs`, because clap can't
+    // tell whether an optional flattened group is present if it contains another flattened group.
     #[clap(flatten)]
-    validate_args: Option<ValidateArgs>,
+    mod_args: Option<ModArgs>,
+
+    #[clap(flatten)]
+    validate_args: ValidateArgs,
 }
 
 #[derive(Subcommand)]
@@ -35,10 +46,26 @@ enum Commands {
         /// release version (e.g. 0.9.3)
         version: Option<String>,
     },
+    /// Tools for working with the mod's localization files.
+    #[command(subcommand)]
+    Loca(LocaCommands),
+    /// Tools for inspecting the mod's gui files.
+    #[command(subcommand)]
+    Gui(GuiCommands),
+    /// Print the range of values that a script value can have. Parts of the calculation that
+    /// depend on the game state are treated as unknown.
+    Eval(EvalArgs),
+    /// Print the script that the scripted triggers and effects called on a line run, with their
+    /// macro arguments filled in and the scripted triggers and effects inside them expanded too.
+    Expand(ExpandArgs),
+    /// Tools for looking up how things in the mod are defined.
+    #[command(subcommand)]
+    Query(QueryCommands),
 }
 
+/// The arguments needed to locate and load a mod, shared between validation and the subcommands.
 #[derive(Args)]
-struct ValidateArgs {
+pub(crate) struct ModArgs {
     #[cfg(feature = "vic3")]
     /// Path to folder of mod to check.
     modpath: PathBuf,
@@ -54,6 +81,122 @@ struct ValidateArgs {
     /// Path to custom .conf file.
     #[clap(long)]
     config: Option<PathBuf>,
+    /// Path to a directory with game tables made by the `extract-tables` utility.
+    /// This lets tiger know about builtin items from game versions newer than itself.
+    #[clap(long)]
+    game_data: Option<PathBuf>,
+}
+
+impl ModArgs {
+    /// Find the game directory, either from the `--game` option or from the Steam library,
+    /// and check that it looks like the right game.
+    fn locate_game(&mut self, game_consts: &GameConsts) -> Result<()> {
+        let &GameConsts { name_short, app_id, signature_file, .. } = game_consts;
+
+        if self.game.is_none() {
+            self.game = find_game_directory_steam(app_id).ok();
+        }
+        if let Some(ref mut game) = self.game {
+            eprintln!("Using {name_short} directory: {}", game.display());
+            let mut sig = game.clone();
+            sig.push(signature_file);
+            if !sig.is_file() {
+                eprintln!("That does not look like a {name_short} directory.");
+                game.push("..");
+                eprintln!("Trying: {}", game.display());
+                sig.clone_from(game);
+                sig.push(signature_file);
+                if sig.is_file() {
+                    eprintln!("Ok.");
+                } else {
+                    bail!("Cannot find {name_short} directory. Please supply it as the --game option.");
+                }
+            }
+        } else {
+            bail!("Cannot find {name_short} directory. Please supply it as the --game option.");
+        }
+        if let Some(version) = self.game.as_deref().and_then(detect_game_version) {
+            eprintln!("Detected {name_short} version {version}.");
+            set_installed_version(version);
+        }
+        Ok(())
+    }
+
+    /// Create an [`Everything`] for the mod, ready to be loaded.
+    fn everything(&mut self) -> Result<Everything> {
+        if let Some(ref dir) = self.game_data {
+            let game_version = GameData::load(dir)?;
+            eprintln!("Using game data for version {game_version} from: {}", dir.display());
+        }
+
+        #[cfg(any(feature = "ck3", feature = "imperator"))]
+        {
+            if self.modpath.is_dir() {
+                self.modpath.push("descriptor.mod");
+            }
+
+            let modfile = ModFile::read(&self.modpath)?;
+            let modpath = modfile.modpath();
+            if !modpath.exists() {
+                eprintln!("Looking for mod in {}", modpath.display());
+                bail!("Cannot find mod directory. Please make sure the .mod file is correct.");
+            }
+            eprintln!("Using mod directory: {}", modpath.display());
+
+            Everything::new(
+                self.config.as_deref(),
+                self.game.as_deref(),
+                &modpath,
+                modfile.replace_paths(),
+            )
+        }
+        #[cfg(feature = "vic3")]
+        {
+            let metadata = ModMetadata::read(&self.modpath)?;
+            eprintln!("Using mod directory: {}", metadata.modpath().display());
+
+            Everything::new(
+                self.config.as_deref(),
+                self.game.as_deref(),
+                &self.modpath,
+                metadata.replace_paths(),
+            )
+        }
+    }
+
+    /// Locate the game, then create an [`Everything`] for the mod and load all its files.
+    ///
+    /// This is for the subcommands, which need the loaded data but don't do a validation run.
+    pub(crate) fn load(&mut self, game_consts: &GameConsts) -> Result<Everything> {
+        self.locate_game(game_consts)?;
+        self.config = validate_config_file(self.config.take());
+        let mut everything = self.everything()?;
+        everything.load_all();
+        Ok(everything)
+    }
+
+    /// Load the mod like [`Self::load`], then print what `query` returns for it.
+    ///
+    /// Only the reports made by `query` are shown, not the ones from loading the mod.
+    pub(crate) fn print_query<F>(&mut self, game_consts: &GameConsts, query: F) -> Result<()>
+    where
+        F: FnOnce(&Everything) -> Result<String>,
+    {
+        let everything = self.load(game_consts)?;
+        drop(take_reports());
+
+        let output = query(&everything)?;
+        print!("{output}");
+        emit_reports(false);
+
+        // Properly dropping `everything` takes a noticeable amount of time, and we're exiting anyway.
+        forget(everything);
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+struct ValidateArgs {
     /// Show errors in the base game script code as well
     #[clap(long)]
     show_vanilla: bool,
@@ -77,24 +220,37 @@ struct ValidateArgs {
     /// Load a JSON file of reports to remove from the output.
     #[clap(long)]
     suppress: Option<PathBuf>,
+    /// Write a list of the defines that the mod changes, with their vanilla and new values, to
+    /// this file.
+    #[clap(long)]
+    defines_diff: Option<PathBuf>,
+    /// Path to the game directory of an older game version. Reports the changes the game made
+    /// since then to the base game files that the mod replaces.
+    #[clap(long)]
+    drift: Option<PathBuf>,
 }
 
 /// Run the main tiger application.
 ///
 /// It provides a number of command line arguments, as well as self-updating capability with the `update` subcommand.
-#[allow(clippy::missing_panics_doc)] // it thinks we can panic on cli.validate_args.unwrap()
+#[allow(clippy::missing_panics_doc)] // it thinks we can panic on cli.mod_args.unwrap()
 pub fn run(game_consts: &GameConsts, current_version: &str) -> Result<()> {
-    let &GameConsts { name, name_short, version, app_id, signature_file, .. } = game_consts;
+    let &GameConsts { name, version, .. } = game_consts;
     let cli = Cli::parse();
 
-    #[allow(clippy::single_match_else)]
     match cli.command {
         Some(Commands::Update { version: target_version }) => {
             update(current_version, target_version.as_deref())?;
             Ok(())
         }
+        Some(Commands::Loca(command)) => run_loca(game_consts, command),
+        Some(Commands::Gui(command)) => run_gui(game_consts, command),
+        Some(Commands::Eval(args)) => run_eval(game_consts, args),
+        Some(Commands::Expand(args)) => run_expand(game_consts, args),
+        Some(Commands::Query(command)) => run_query(game_consts, command),
         None => {
-            let mut args = cli.validate_args.unwrap();
+            let mut mod_args = cli.mod_args.unwrap();
+            let args = cli.validate_args;
             #[cfg(windows)]
             if !args.no_color {
                 let _ = ansiterm::enable_ansi_support()
@@ -105,30 +261,17 @@ pub fn run(game_consts: &GameConsts, current_version: &str) -> Result<()> {
             eprintln!("If you are using a newer version of {name}, it may be inaccurate.");
             eprintln!("!! Currently it's inaccurate anyway because it's in beta state.");
 
-            if args.game.is_none() {
-                args.game = find_game_directory_steam(app_id).ok();
-            }
-            if let Some(ref mut game) = args.game {
-                eprintln!("Using {name_short} directory: {}", game.display());
-                let mut sig = game.clone();
-                sig.push(signature_file);
-                if !sig.is_file() {
-                    eprintln!("That does not look like a {name_short} directory.");
-                    game.push("..");
-                    eprintln!("Trying: {}", game.display());
-                    sig.clone_from(game);
-                    sig.push(signature_file);
-                    if sig.is_file() {
-                        eprintln!("Ok.");
-                    } else {
-                        bail!("Cannot find {name_short} directory. Please supply it as the --game option.");
-                    }
-                }
-            } else {
-                bail!("Cannot find {name_short} directory. Please supply it as the --game option.");
+            mod_args.locate_game(game_consts)?;
+            let outdated = outdated_tables();
+            if !outdated.is_empty() {
+                eprintln!(
+                    "The built-in tables for {} are older than the installed game.",
+                    outdated.join(", ")
+                );
+                eprintln!("Reports that depend on them will have weak confidence.");
             }
 
-            args.config = validate_config_file(args.config);
+            mod_args.config = validate_config_file(mod_args.config);
 
             if let Some(suppress) = args.suppress {
                 eprintln!("Suppressing reports from: {}", suppress.display());
@@ -159,41 +302,7 @@ pub fn run(game_consts: &GameConsts, current_version: &str) -> Result<()> {
                 disable_ansi_colors();
             }
 
-            let mut everything;
-
-            #[cfg(any(feature = "ck3", feature = "imperator"))]
-            {
-                if args.modpath.is_dir() {
-                    args.modpath.push("descriptor.mod");
-                }
-
-                let modfile = ModFile::read(&args.modpath)?;
-                let modpath = modfile.modpath();
-                if !modpath.exists() {
-                    eprintln!("Looking for mod in {}", modpath.display());
-                    bail!("Cannot find mod directory. Please make sure the .mod file is correct.");
-                }
-                eprintln!("Using mod directory: {}", modpath.display());
-
-                everything = Everything::new(
-                    args.config.as_deref(),
-                    args.game.as_deref(),
-                    &modpath,
-                    modfile.replace_paths(),
-                )?;
-            }
-            #[cfg(feature = "vic3")]
-            {
-                let metadata = ModMetadata::read(&args.modpath)?;
-                eprintln!("Using mod directory: {}", metadata.modpath().display());
-
-                everything = Everything::new(
-                    args.config.as_deref(),
-                    args.game.as_deref(),
-                    &args.modpath,
-                    metadata.replace_paths(),
-                )?;
-            }
+            let mut everything = mod_args.everything()?;
 
             // Print a blank line between the preamble and the first report:
             eprintln!();
@@ -221,6 +330,9 @@ pub fn run(game_consts: &GameConsts, current_version: &str) -> Result<()> {
             everything.load_all();
             everything.validate_all();
             everything.check_rivers();
+            if let Some(old_dir) = &args.drift {
+                everything.check_drift(old_dir)?;
+            }
 
             #[cfg(feature = "ck3")]
             if args.pod {
@@ -230,6 +342,10 @@ pub fn run(game_consts: &GameConsts, current_version: &str) -> Result<()> {
             if args.unused {
                 everything.check_unused();
             }
+            if let Some(path) = args.defines_diff {
+                write(&path, everything.defines_diff())?;
+                eprintln!("Wrote the changed defines to: {}", path.display());
+            }
 
             // Properly dropping `everything` takes a noticeable amount of time, and we're exiting anyway.
             forget(everything);
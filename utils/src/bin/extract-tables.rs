@@ -0,0 +1,424 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::fs::{read_dir, read_to_string, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use strum_macros::Display;
+
+/// The version of the output format. Must match `GAME_DATA_FORMAT` in tiger-lib.
+const FORMAT_VERSION: u32 = 1;
+
+/// The name of the output file. Must match `GAME_DATA_FILE` in tiger-lib.
+const OUTPUT_FILE: &str = "game_data.txt";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum, Display)]
+#[strum(serialize_all = "lowercase")]
+enum Game {
+    /// Crusader Kings 3
+    Ck3,
+    /// Victoria 3
+    Vic3,
+    /// Imperator: Rome
+    Imperator,
+}
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Which game the logs are for
+    #[arg(long)]
+    game: Game,
+
+    /// The game version the logs were made with, such as 1.14.0.2
+    #[arg(long)]
+    game_version: String,
+
+    /// Directory with the `script_docs` logs (input)
+    #[arg(long)]
+    logs: PathBuf,
+
+    /// Directory with object browser dumps, one name per line, such as `sounds.txt` (input)
+    #[arg(long)]
+    object_browser: Option<PathBuf>,
+
+    /// The game's install directory, to read the supported languages and map painting modes (input)
+    #[arg(long)]
+    game_dir: Option<PathBuf>,
+
+    /// Directory to write the game data file to (output)
+    #[arg(long)]
+    out: PathBuf,
+}
+
+/// Everything extracted from the logs. `BTreeMap` is used so that the output is stable and can be
+/// diffed between game versions.
+#[derive(Debug, Default)]
+struct Tables {
+    triggers: BTreeMap<String, String>,
+    effects: BTreeMap<String, String>,
+    event_targets: BTreeMap<String, (String, String)>,
+    modifiers: BTreeSet<String>,
+    on_actions: BTreeMap<String, String>,
+    lists: BTreeMap<String, BTreeSet<String>>,
+    languages: BTreeSet<String>,
+}
+
+/// Split a log into its entries, which are separated by lines of dashes.
+/// The title line at the top of the log is removed.
+fn log_entries(content: &str) -> Vec<Vec<&str>> {
+    let mut entries = Vec::new();
+    let mut entry = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.len() > 3 && line.chars().all(|c| c == '-') {
+            entries.push(std::mem::take(&mut entry));
+        } else if !line.is_empty() && !line.ends_with("Documentation:") && line != "Event Targets:"
+        {
+            entry.push(line);
+        }
+    }
+    entries.push(entry);
+    entries.retain(|entry| !entry.is_empty());
+    entries
+}
+
+/// Get the item name from the first line of an entry, which looks like `name - description`,
+/// `name = { ... } - description`, or `name:`.
+fn entry_name(header: &str) -> Option<&str> {
+    let name = header.split(|c: char| c.is_whitespace() || c == '=' || c == ':').next()?;
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(name)
+}
+
+/// Find a line like `Supported Scopes: character, landed title` and convert the scopes to the
+/// form tiger reads, which is `character|landed_title`.
+fn entry_scopes(entry: &[&str], field: &str) -> Option<String> {
+    for line in entry {
+        if let Some(scopes) = line.strip_prefix(field).and_then(|s| s.strip_prefix(':')) {
+            let scopes: Vec<_> = scopes
+                .split(',')
+                .map(|s| s.trim().to_lowercase().replace(' ', "_"))
+                .filter(|s| !s.is_empty())
+                .collect();
+            if scopes.is_empty() {
+                return None;
+            }
+            return Some(scopes.join("|"));
+        }
+    }
+    None
+}
+
+fn read_log(logs: &Path, name: &str) -> Result<Option<String>> {
+    let path = logs.join(name);
+    if !path.exists() {
+        eprintln!("Skipping {name} because it does not exist");
+        return Ok(None);
+    }
+    read_to_string(&path).with_context(|| format!("reading {}", path.display())).map(Some)
+}
+
+fn load_scoped_items(content: &str, field: &str, table: &mut BTreeMap<String, String>) {
+    for entry in log_entries(content) {
+        if let Some(name) = entry_name(entry[0]) {
+            let scopes = entry_scopes(&entry, field).unwrap_or_else(|| "all".to_owned());
+            table.insert(name.to_owned(), scopes);
+        }
+    }
+}
+
+fn load_event_targets(content: &str, tables: &mut Tables) {
+    for entry in log_entries(content) {
+        if let Some(name) = entry_name(entry[0]) {
+            let input = entry_scopes(&entry, "Input Scopes").unwrap_or_else(|| "all".to_owned());
+            let output = entry_scopes(&entry, "Output Scopes").unwrap_or_else(|| "all".to_owned());
+            tables.event_targets.insert(name.to_owned(), (input, output));
+        }
+    }
+}
+
+/// Modifiers are listed one per line, like `Tag: monthly_prestige, Categories: character`.
+fn load_modifiers(content: &str, tables: &mut Tables) {
+    for line in content.lines() {
+        if let Some(rest) = line.trim().strip_prefix("Tag: ") {
+            let name = rest.split(',').next().unwrap_or(rest).trim();
+            if !name.is_empty() {
+                tables.modifiers.insert(name.to_owned());
+            }
+        }
+    }
+}
+
+fn load_object_browser(dir: &Path, tables: &mut Tables) -> Result<()> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension() != Some(OsStr::new("txt")) {
+            continue;
+        }
+        let Some(list) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let content =
+            read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        let names = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(ToOwned::to_owned);
+        tables.lists.entry(list).or_default().extend(names);
+    }
+    Ok(())
+}
+
+/// Each language has a subdirectory in the game's `localization` directory, such as `english`.
+fn load_languages(game_dir: &Path, tables: &mut Tables) -> Result<()> {
+    let dir = game_dir.join("game/localization");
+    for entry in read_dir(&dir).with_context(|| format!("reading {}", dir.display()))? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        // `replace` directories hold overrides for other languages' files.
+        if name != "replace" && name.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+            tables.languages.insert(name);
+        }
+    }
+    Ok(())
+}
+
+/// Collect the values of the `map_painting_mode` fields in a map modes file, such as
+/// `map_painting_mode_secondary = "countries"`. The base game's map modes use all of them.
+fn load_map_painting_modes(content: &str, tables: &mut Tables) {
+    let modes = tables.lists.entry("map_painting_modes".to_owned()).or_default();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if !key.trim().starts_with("map_painting_mode") {
+            continue;
+        }
+        let value = value.trim().trim_matches('"');
+        if !value.is_empty() && value.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            modes.insert(value.to_owned());
+        }
+    }
+}
+
+fn load_game_dir(game_dir: &Path, game: Game, tables: &mut Tables) -> Result<()> {
+    load_languages(game_dir, tables)?;
+    if game == Game::Vic3 {
+        let dir = game_dir.join("game/gfx/map/map_modes");
+        for entry in read_dir(&dir).with_context(|| format!("reading {}", dir.display()))? {
+            let path = entry?.path();
+            if path.extension() == Some(OsStr::new("txt")) {
+                let content =
+                    read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+                load_map_painting_modes(&content, tables);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Quote a value if it can't be written bare in script.
+fn quoted(s: &str) -> String {
+    if s.chars().all(|c| c.is_alphanumeric() || "_|.:".contains(c)) {
+        s.to_owned()
+    } else {
+        format!("\"{}\"", s.replace('"', "\\\""))
+    }
+}
+
+fn write_tables(tables: &Tables, args: &Cli) -> Result<()> {
+    let path = args.out.join(OUTPUT_FILE);
+    let mut outf = File::create(&path).with_context(|| format!("creating {}", path.display()))?;
+    writeln!(outf, "# Generated by extract-tables. Do not edit.")?;
+    writeln!(outf, "format_version = {FORMAT_VERSION}")?;
+    writeln!(outf, "game = {}", args.game)?;
+    writeln!(outf, "game_version = {}", quoted(&args.game_version))?;
+
+    for (name, table) in [
+        ("triggers", &tables.triggers),
+        ("effects", &tables.effects),
+        ("on_actions", &tables.on_actions),
+    ] {
+        writeln!(outf, "{name} = {{")?;
+        for (key, scopes) in table {
+            writeln!(outf, "\t{key} = {}", quoted(scopes))?;
+        }
+        writeln!(outf, "}}")?;
+    }
+
+    writeln!(outf, "event_targets = {{")?;
+    for (key, (input, output)) in &tables.event_targets {
+        writeln!(outf, "\t{key} = {{ input = {} output = {} }}", quoted(input), quoted(output))?;
+    }
+    writeln!(outf, "}}")?;
+
+    writeln!(outf, "modifiers = {{")?;
+    for key in &tables.modifiers {
+        writeln!(outf, "\t{}", quoted(key))?;
+    }
+    writeln!(outf, "}}")?;
+
+    writeln!(outf, "lists = {{")?;
+    for (name, list) in &tables.lists {
+        writeln!(outf, "\t{} = {{", quoted(name))?;
+        for item in list {
+            writeln!(outf, "\t\t{}", quoted(item))?;
+        }
+        writeln!(outf, "\t}}")?;
+    }
+    writeln!(outf, "}}")?;
+
+    writeln!(outf, "languages = {{")?;
+    for language in &tables.languages {
+        writeln!(outf, "\t{}", quoted(language))?;
+    }
+    writeln!(outf, "}}")?;
+    eprintln!("Wrote {}", path.display());
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    if !args.logs.is_dir() {
+        bail!("{} is not a directory", args.logs.display());
+    }
+
+    let mut tables = Tables::default();
+    if let Some(content) = read_log(&args.logs, "triggers.log")? {
+        load_scoped_items(&content, "Supported Scopes", &mut tables.triggers);
+    }
+    if let Some(content) = read_log(&args.logs, "effects.log")? {
+        load_scoped_items(&content, "Supported Scopes", &mut tables.effects);
+    }
+    if let Some(content) = read_log(&args.logs, "event_targets.log")? {
+        load_event_targets(&content, &mut tables);
+    }
+    if let Some(content) = read_log(&args.logs, "modifiers.log")? {
+        load_modifiers(&content, &mut tables);
+    }
+    if let Some(content) = read_log(&args.logs, "on_actions.log")? {
+        load_scoped_items(&content, "Expected Scope", &mut tables.on_actions);
+    }
+    if let Some(dir) = &args.object_browser {
+        load_object_browser(dir, &mut tables)?;
+    }
+    if let Some(dir) = &args.game_dir {
+        load_game_dir(dir, args.game, &mut tables)?;
+    }
+
+    eprintln!(
+        "Found {} triggers, {} effects, {} event targets, {} modifiers, {} on-actions, {} languages",
+        tables.triggers.len(),
+        tables.effects.len(),
+        tables.event_targets.len(),
+        tables.modifiers.len(),
+        tables.on_actions.len(),
+        tables.languages.len()
+    );
+    write_tables(&tables, &args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIGGERS_LOG: &str = "Trigger Documentation:
+is_adult - is the character an adult?
+is_adult = yes
+Supported Scopes: character
+--------------------
+has_title = { title = x } - does the character hold the title?
+Supported Scopes: character, landed title
+Supported Targets: landed title
+--------------------
+always - always true or false
+";
+
+    #[test]
+    fn test_log_entries() {
+        let entries = log_entries(TRIGGERS_LOG);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0][0], "is_adult - is the character an adult?");
+        assert_eq!(entries[2], vec!["always - always true or false"]);
+    }
+
+    #[test]
+    fn test_entry_name() {
+        assert_eq!(entry_name("is_adult - is the character an adult?"), Some("is_adult"));
+        assert_eq!(entry_name("has_title = { title = x } - text"), Some("has_title"));
+        assert_eq!(entry_name("on_birth:"), Some("on_birth"));
+        assert_eq!(entry_name("<scope> - text"), None);
+        assert_eq!(entry_name(""), None);
+    }
+
+    #[test]
+    fn test_scoped_items() {
+        let mut table = BTreeMap::new();
+        load_scoped_items(TRIGGERS_LOG, "Supported Scopes", &mut table);
+        assert_eq!(table["is_adult"], "character");
+        assert_eq!(table["has_title"], "character|landed_title");
+        assert_eq!(table["always"], "all");
+    }
+
+    #[test]
+    fn test_event_targets() {
+        let log = "Event Targets:
+--------------------
+liege - the character's liege
+Input Scopes: character
+Output Scopes: character
+--------------------
+capital_province - the capital
+Input Scopes: character, landed title
+--------------------
+";
+        let mut tables = Tables::default();
+        load_event_targets(log, &mut tables);
+        assert_eq!(tables.event_targets["liege"], ("character".to_owned(), "character".to_owned()));
+        assert_eq!(
+            tables.event_targets["capital_province"],
+            ("character|landed_title".to_owned(), "all".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_modifiers() {
+        let log =
+            "Modifiers:\nTag: monthly_prestige, Categories: character\nTag: tax_mult\nnot a tag\n";
+        let mut tables = Tables::default();
+        load_modifiers(log, &mut tables);
+        let expected: BTreeSet<String> =
+            ["monthly_prestige", "tax_mult"].map(ToOwned::to_owned).into();
+        assert_eq!(tables.modifiers, expected);
+    }
+
+    #[test]
+    fn test_map_painting_modes() {
+        let content = "map_mode = {
+    map_painting_mode = \"countries\"
+    map_painting_mode_secondary = gdp # comment
+    # map_painting_mode = commented_out
+    map_names = states
+}";
+        let mut tables = Tables::default();
+        load_map_painting_modes(content, &mut tables);
+        let expected: BTreeSet<String> = ["countries", "gdp"].map(ToOwned::to_owned).into();
+        assert_eq!(tables.lists["map_painting_modes"], expected);
+    }
+
+    #[test]
+    fn test_quoted() {
+        assert_eq!(quoted("character|province"), "character|province");
+        assert_eq!(quoted("1.14.0.2"), "1.14.0.2");
+        assert_eq!(quoted("event:/SFX/a b"), "\"event:/SFX/a b\"");
+        assert_eq!(quoted("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+}